@@ -1,11 +1,14 @@
 #[cfg(not(feature = "library"))]
 use cosmwasm_std::entry_point;
-use cosmwasm_std::{to_binary, Binary, Deps, DepsMut, Env, MessageInfo, Response, StdResult};
+use cosmwasm_std::{
+    to_binary, Binary, Deps, DepsMut, Empty, Env, MessageInfo, Response, StdResult,
+};
 use cw2::set_contract_version;
+use shared::access;
 
 use crate::error::ContractError;
 use crate::msg::{CountResponse, ExecuteMsg, InstantiateMsg, QueryMsg};
-use crate::state::{State, STATE};
+use crate::state::{State, LEGACY_STATE, STATE};
 
 // version info for migration info
 const CONTRACT_NAME: &str = "crates.io:counter";
@@ -18,12 +21,10 @@ pub fn instantiate(
     info: MessageInfo,
     msg: InstantiateMsg,
 ) -> Result<Response, ContractError> {
-    let state = State {
-        count: msg.count,
-        owner: info.sender.clone(),
-    };
+    let state = State { count: msg.count };
     set_contract_version(deps.storage, CONTRACT_NAME, CONTRACT_VERSION)?;
     STATE.save(deps.storage, &state)?;
+    access::initialize_owner(deps.storage, &info.sender)?;
 
     Ok(Response::new()
         .add_attribute("method", "instantiate")
@@ -41,6 +42,7 @@ pub fn execute(
     match msg {
         ExecuteMsg::Increment {} => try_increment(deps),
         ExecuteMsg::Reset { count } => try_reset(deps, info, count),
+        ExecuteMsg::Access(msg) => Ok(access::execute(deps, info, msg)?),
     }
 }
 
@@ -53,20 +55,37 @@ pub fn try_increment(deps: DepsMut) -> Result<Response, ContractError> {
     Ok(Response::new().add_attribute("method", "try_increment"))
 }
 pub fn try_reset(deps: DepsMut, info: MessageInfo, count: i32) -> Result<Response, ContractError> {
+    access::assert_owner(deps.storage, &info.sender)?;
     STATE.update(deps.storage, |mut state| -> Result<_, ContractError> {
-        if info.sender != state.owner {
-            return Err(ContractError::Unauthorized {});
-        }
         state.count = count;
         Ok(state)
     })?;
     Ok(Response::new().add_attribute("method", "reset"))
 }
 
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn migrate(deps: DepsMut, _env: Env, _msg: Empty) -> Result<Response, ContractError> {
+    // Counters instantiated before shared::access kept their owner in STATE
+    if access::OWNER.may_load(deps.storage)?.is_none() {
+        let legacy = LEGACY_STATE.load(deps.storage)?;
+        STATE.save(
+            deps.storage,
+            &State {
+                count: legacy.count,
+            },
+        )?;
+        access::initialize_owner(deps.storage, &legacy.owner)?;
+    }
+    set_contract_version(deps.storage, CONTRACT_NAME, CONTRACT_VERSION)?;
+
+    Ok(Response::new().add_attribute("method", "migrate"))
+}
+
 #[cfg_attr(not(feature = "library"), entry_point)]
 pub fn query(deps: Deps, _env: Env, msg: QueryMsg) -> StdResult<Binary> {
     match msg {
         QueryMsg::GetCount {} => to_binary(&query_count(deps)?),
+        QueryMsg::Access(msg) => access::query(deps, msg),
     }
 }
 
@@ -78,8 +97,11 @@ fn query_count(deps: Deps) -> StdResult<CountResponse> {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::state::LegacyState;
     use cosmwasm_std::testing::{mock_dependencies, mock_env, mock_info};
+    use cosmwasm_std::Addr;
     use cosmwasm_std::{coins, from_binary};
+    use shared::access::{AccessError, AccessExecuteMsg};
 
     #[test]
     fn proper_initialization() {
@@ -130,7 +152,7 @@ mod tests {
         let msg = ExecuteMsg::Reset { count: 5 };
         let res = execute(deps.as_mut(), mock_env(), unauth_info, msg);
         match res {
-            Err(ContractError::Unauthorized {}) => {}
+            Err(ContractError::Access(AccessError::Unauthorized {})) => {}
             _ => panic!("Must return unauthorized error"),
         }
 
@@ -144,4 +166,65 @@ mod tests {
         let value: CountResponse = from_binary(&res).unwrap();
         assert_eq!(5, value.count);
     }
+
+    #[test]
+    fn new_owner_can_reset() {
+        let mut deps = mock_dependencies(&[]);
+
+        let msg = InstantiateMsg { count: 17 };
+        let info = mock_info("creator", &[]);
+        let _res = instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+        let msg = ExecuteMsg::Access(AccessExecuteMsg::TransferOwnership {
+            new_owner: "successor".to_string(),
+        });
+        let _res = execute(deps.as_mut(), mock_env(), mock_info("creator", &[]), msg).unwrap();
+        let msg = ExecuteMsg::Access(AccessExecuteMsg::AcceptOwnership {});
+        let _res = execute(deps.as_mut(), mock_env(), mock_info("successor", &[]), msg).unwrap();
+
+        // the creator lost the right to reset with the ownership
+        let msg = ExecuteMsg::Reset { count: 5 };
+        let res = execute(deps.as_mut(), mock_env(), mock_info("creator", &[]), msg);
+        assert!(matches!(
+            res,
+            Err(ContractError::Access(AccessError::Unauthorized {}))
+        ));
+
+        let msg = ExecuteMsg::Reset { count: 5 };
+        let _res = execute(deps.as_mut(), mock_env(), mock_info("successor", &[]), msg).unwrap();
+        let res = query(deps.as_ref(), mock_env(), QueryMsg::GetCount {}).unwrap();
+        let value: CountResponse = from_binary(&res).unwrap();
+        assert_eq!(5, value.count);
+    }
+
+    #[test]
+    fn migrate_moves_the_owner_into_access() {
+        let mut deps = mock_dependencies(&[]);
+        let legacy = LegacyState {
+            count: 17,
+            owner: Addr::unchecked("creator"),
+        };
+        LEGACY_STATE.save(deps.as_mut().storage, &legacy).unwrap();
+
+        let _res = migrate(deps.as_mut(), mock_env(), Empty {}).unwrap();
+        let res = query(deps.as_ref(), mock_env(), QueryMsg::GetCount {}).unwrap();
+        let value: CountResponse = from_binary(&res).unwrap();
+        assert_eq!(17, value.count);
+
+        // only the old owner can still reset
+        let msg = ExecuteMsg::Reset { count: 5 };
+        let res = execute(deps.as_mut(), mock_env(), mock_info("anyone", &[]), msg);
+        assert!(matches!(
+            res,
+            Err(ContractError::Access(AccessError::Unauthorized {}))
+        ));
+        let msg = ExecuteMsg::Reset { count: 5 };
+        let _res = execute(deps.as_mut(), mock_env(), mock_info("creator", &[]), msg).unwrap();
+
+        // migrating again leaves everything as it is
+        let _res = migrate(deps.as_mut(), mock_env(), Empty {}).unwrap();
+        let res = query(deps.as_ref(), mock_env(), QueryMsg::GetCount {}).unwrap();
+        let value: CountResponse = from_binary(&res).unwrap();
+        assert_eq!(5, value.count);
+    }
 }
@@ -7,7 +7,16 @@ use cw_storage_plus::Item;
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 pub struct State {
     pub count: i32,
-    pub owner: Addr,
 }
 
 pub const STATE: Item<State> = Item::new("state");
+
+// State as stored before the owner moved to shared::access, only read by
+// `migrate`
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct LegacyState {
+    pub count: i32,
+    pub owner: Addr,
+}
+
+pub const LEGACY_STATE: Item<LegacyState> = Item::new("state");
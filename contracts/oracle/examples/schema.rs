@@ -1,9 +1,15 @@
 use std::env::current_dir;
 use std::fs::create_dir_all;
 
-use cosmwasm_schema::{export_schema, remove_schemas, schema_for};
+use cosmwasm_schema::{export_schema, export_schema_with_title, remove_schemas, schema_for};
 
-use oracle::msg::{ExecuteMsg, InstantiateMsg, PriceResponse, QueryMsg};
+use oracle::msg::{
+    Attestation, BondResponse, BondingConfigResponse, BondsResponse, EmaResponse, ExecuteMsg,
+    FeedHealthResponse, FeedHealthsResponse, FeedResponse, FeedsResponse, HookMsg, InstantiateMsg,
+    PendingPricesResponse, PriceHistoryResponse, PriceResponse, PriceResponseV2, PublisherResponse,
+    PublishersResponse, QueryMsg, RolesResponse, RoundResponse, RoundsResponse, SignersResponse,
+    SlashesResponse, SubscribersResponse, TwapResponse,
+};
 use oracle::state::State;
 
 fn main() {
@@ -16,5 +22,27 @@ fn main() {
     export_schema(&schema_for!(ExecuteMsg), &out_dir);
     export_schema(&schema_for!(QueryMsg), &out_dir);
     export_schema(&schema_for!(State), &out_dir);
-    export_schema(&schema_for!(PriceResponse), &out_dir);
+    export_schema_with_title(&schema_for!(PriceResponse), &out_dir, "PriceResponse");
+    export_schema_with_title(&schema_for!(PriceResponseV2), &out_dir, "PriceResponseV2");
+    export_schema(&schema_for!(PriceHistoryResponse), &out_dir);
+    export_schema(&schema_for!(TwapResponse), &out_dir);
+    export_schema(&schema_for!(EmaResponse), &out_dir);
+    export_schema(&schema_for!(PendingPricesResponse), &out_dir);
+    export_schema(&schema_for!(FeedResponse), &out_dir);
+    export_schema(&schema_for!(FeedsResponse), &out_dir);
+    export_schema(&schema_for!(FeedHealthResponse), &out_dir);
+    export_schema(&schema_for!(FeedHealthsResponse), &out_dir);
+    export_schema(&schema_for!(PublisherResponse), &out_dir);
+    export_schema(&schema_for!(PublishersResponse), &out_dir);
+    export_schema(&schema_for!(SignersResponse), &out_dir);
+    export_schema(&schema_for!(RoundResponse), &out_dir);
+    export_schema(&schema_for!(RoundsResponse), &out_dir);
+    export_schema(&schema_for!(SubscribersResponse), &out_dir);
+    export_schema(&schema_for!(HookMsg), &out_dir);
+    export_schema(&schema_for!(RolesResponse), &out_dir);
+    export_schema(&schema_for!(BondingConfigResponse), &out_dir);
+    export_schema(&schema_for!(BondResponse), &out_dir);
+    export_schema(&schema_for!(BondsResponse), &out_dir);
+    export_schema(&schema_for!(SlashesResponse), &out_dir);
+    export_schema(&schema_for!(Attestation), &out_dir);
 }
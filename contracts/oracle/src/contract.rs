@@ -1,178 +1,4886 @@
 #[cfg(not(feature = "library"))]
 use cosmwasm_std::entry_point;
 use cosmwasm_std::{
-    to_binary, Binary, Deps, DepsMut, Env, MessageInfo, Response, StdError, StdResult,
+    coins, from_binary, to_binary, Addr, BankMsg, Binary, ContractResult, Decimal, Deps, DepsMut,
+    Empty, Env, Event, Fraction, MessageInfo, Order, Reply, Response, StdError, StdResult, Storage,
+    SubMsg, Timestamp, Uint128, Uint256, WasmMsg,
 };
+use cw0::must_pay;
 use cw2::set_contract_version;
+use cw_storage_plus::{Bound, U64Key};
+use sha2::{Digest, Sha256};
+use shared::access::{self, AccessError};
+use std::cmp::Reverse;
+use std::convert::TryFrom;
+use terra_cosmwasm::TerraQuerier;
 
 use crate::error::ContractError;
-use crate::msg::{ExecuteMsg, InstantiateMsg, PriceResponse, QueryMsg};
-use crate::state::{State, STATE};
+use crate::msg::{
+    AssetId, Attestation, BondResponse, BondingConfig, BondingConfigResponse, BondsResponse,
+    EmaResponse, ExecuteMsg, FeedHealthResponse, FeedHealthsResponse, FeedId, FeedResponse,
+    FeedStatus, FeedsResponse, HookMsg, InstantiateMsg, NativeReference, NativeSource,
+    PendingPriceResponse, PendingPricesResponse, PriceBounds, PriceHistoryResponse, PriceKind,
+    PriceResponse, PriceResponseV2, PublisherResponse, PublishersResponse, QueryMsg, RateLimit,
+    RolesResponse, RoundConfig, RoundPhase, RoundResponse, RoundsResponse, SignerResponse,
+    SignersResponse, SlashesResponse, SubscriberResponse, SubscribersResponse, TwapResponse,
+    GUARDIAN_ROLE,
+};
+use crate::state::{
+    feed_key, Bond, Bonding, Commitment, Ema, Feed, LastAttestation, Observation, Round, Signer,
+    Slash, State, Submission, Subscription, Unbonding, BONDS, COMMITMENTS, FEEDS, HISTORY,
+    LAST_ATTESTATIONS, PENDING, PUBLISHERS, ROUNDS, SIGNERS, SLASHES, STATE, SUBMISSIONS,
+    SUBSCRIBERS,
+};
 
 // version info for migration info
 const CONTRACT_NAME: &str = "crates.io:oracle";
 const CONTRACT_VERSION: &str = env!("CARGO_PKG_VERSION");
 
+const DEFAULT_MAX_PRICE_AGE: u64 = 60 * 60;
+const DEFAULT_HISTORY_SIZE: u32 = 100;
+const DEFAULT_EMA_HALF_LIFE: u64 = 15 * 60;
+const DEFAULT_HEARTBEAT: u64 = 5 * 60;
+
+const BOND_DENOM: &str = "uluna";
+
+// ln(2) with 18 decimals
+const LN_2: u128 = 693_147_180_559_945_309;
+
+// Decimal always carries 18 fractional digits
+const MAX_DECIMALS: u8 = 18;
+
+// settings for pagination
+const MAX_LIMIT: u32 = 30;
+const DEFAULT_LIMIT: u32 = 10;
+
+// Gas each subscriber may use handling a hook, so that one subscriber cannot
+// exhaust the gas of the whole update
+const HOOK_GAS_LIMIT: u64 = 300_000;
+const HOOK_REPLY_ID: u64 = 1;
+
 #[cfg_attr(not(feature = "library"), entry_point)]
 pub fn instantiate(
     deps: DepsMut,
-    _env: Env,
+    env: Env,
     info: MessageInfo,
     msg: InstantiateMsg,
 ) -> Result<Response, ContractError> {
     set_contract_version(deps.storage, CONTRACT_NAME, CONTRACT_VERSION)?;
+    let default_feed = validate_feed_id(deps.as_ref(), msg.default_feed)?;
+    let admin = match msg.admin {
+        Some(admin) => deps.api.addr_validate(&admin)?,
+        None => info.sender.clone(),
+    };
     let state = State {
-        owner: info.sender.clone(),
-        price: msg.price,
+        paused: false,
+        bonding: None,
+        slash_count: 0,
+        max_price_age: msg.max_price_age.unwrap_or(DEFAULT_MAX_PRICE_AGE),
+        default_feed: default_feed.clone(),
+        history_size: msg.history_size.unwrap_or(DEFAULT_HISTORY_SIZE).max(1),
     };
 
     STATE.save(deps.storage, &state)?;
+    access::initialize_owner(deps.storage, &admin)?;
+
+    // The instantiator seeds the first price and starts out as a publisher,
+    // which the admin can revoke like any other
+    PUBLISHERS.save(deps.storage, &info.sender, &Empty {})?;
+    for publisher in msg.publishers {
+        let publisher = deps.api.addr_validate(&publisher)?;
+        PUBLISHERS.save(deps.storage, &publisher, &Empty {})?;
+    }
+
+    validate_price(msg.price, msg.decimals)?;
+    let key = feed_key(&default_feed);
+    FEEDS.save(
+        deps.storage,
+        &key,
+        &Feed {
+            id: default_feed,
+            decimals: msg.decimals,
+            history_count: 0,
+            bounds: PriceBounds::default(),
+            rounds: None,
+            round_count: 0,
+            native_reference: None,
+            ema_half_life: DEFAULT_EMA_HALF_LIFE,
+            heartbeat: DEFAULT_HEARTBEAT,
+            rate_limit: None,
+            ema: None,
+        },
+    )?;
+    SUBMISSIONS.save(
+        deps.storage,
+        (&key, &info.sender),
+        &Submission {
+            price: msg.price,
+            block_height: env.block.height,
+            block_time: env.block.time,
+        },
+    )?;
+    record_observation(deps, &env, &key)?;
 
     Ok(Response::new()
         .add_attribute("method", "instantiate")
-        .add_attribute("owner", &state.owner)
-        .add_attribute("price", &state.price.to_string()))
+        .add_attribute("admin", admin)
+        .add_attribute("publisher", info.sender)
+        .add_attribute("price", msg.price.to_string()))
 }
 
 #[cfg_attr(not(feature = "library"), entry_point)]
 pub fn execute(
     deps: DepsMut,
-    _env: Env,
+    env: Env,
     info: MessageInfo,
     msg: ExecuteMsg,
 ) -> Result<Response, ContractError> {
     match msg {
-        ExecuteMsg::UpdatePrice { price } => try_update_price(deps, info, price),
+        ExecuteMsg::UpdatePrice { feed, price } => try_update_price(deps, env, info, feed, price),
+        ExecuteMsg::BatchUpdate { updates } => try_batch_update(deps, env, info, updates),
+        ExecuteMsg::Access(msg) => Ok(access::execute(deps, info, msg)?),
+        ExecuteMsg::SetBondingConfig { config } => try_set_bonding_config(deps, info, config),
+        ExecuteMsg::Bond {} => try_bond(deps, info),
+        ExecuteMsg::Unbond { amount } => try_unbond(deps, env, info, amount),
+        ExecuteMsg::WithdrawUnbonded {} => try_withdraw_unbonded(deps, env, info),
+        ExecuteMsg::Pause {} => try_pause(deps, info),
+        ExecuteMsg::Unpause {} => try_unpause(deps, info),
+        ExecuteMsg::AddPublisher { address } => try_add_publisher(deps, info, address),
+        ExecuteMsg::RemovePublisher { address } => try_remove_publisher(deps, info, address),
+        ExecuteMsg::AddSigner {
+            address,
+            public_key,
+        } => try_add_signer(deps, info, address, public_key),
+        ExecuteMsg::RemoveSigner { address } => try_remove_signer(deps, info, address),
+        ExecuteMsg::SubmitAttestation {
+            signer,
+            payload,
+            signature,
+        } => try_submit_attestation(deps, env, signer, payload, signature),
+        ExecuteMsg::RegisterFeed { feed, decimals } => {
+            try_register_feed(deps, info, feed, decimals)
+        }
+        ExecuteMsg::RemoveFeed { feed } => try_remove_feed(deps, info, feed),
+        ExecuteMsg::SetEmaHalfLife {
+            feed,
+            half_life_seconds,
+        } => try_set_ema_half_life(deps, env, info, feed, half_life_seconds),
+        ExecuteMsg::SetHeartbeat {
+            feed,
+            heartbeat_seconds,
+        } => try_set_heartbeat(deps, info, feed, heartbeat_seconds),
+        ExecuteMsg::SetPriceBounds { feed, bounds } => {
+            try_set_price_bounds(deps, info, feed, bounds)
+        }
+        ExecuteMsg::SetNativeReference { feed, reference } => {
+            try_set_native_reference(deps, info, feed, reference)
+        }
+        ExecuteMsg::SetRateLimit { feed, rate_limit } => {
+            try_set_rate_limit(deps, info, feed, rate_limit)
+        }
+        ExecuteMsg::SetRoundConfig { feed, config } => {
+            try_set_round_config(deps, info, feed, config)
+        }
+        ExecuteMsg::StartRound { feed } => try_start_round(deps, env, info, feed),
+        ExecuteMsg::CommitPrice { feed, commitment } => {
+            try_commit_price(deps, env, info, feed, commitment)
+        }
+        ExecuteMsg::RevealPrice { feed, price, salt } => {
+            try_reveal_price(deps, env, info, feed, price, salt)
+        }
+        ExecuteMsg::FinalizeRound { feed } => try_finalize_round(deps, env, feed),
+        ExecuteMsg::Subscribe { feed } => try_subscribe(deps, info, feed),
+        ExecuteMsg::Unsubscribe { feed } => try_unsubscribe(deps, info, feed),
+        ExecuteMsg::ApproveSubscriber { feed, subscriber } => {
+            try_approve_subscriber(deps, info, feed, subscriber)
+        }
+        ExecuteMsg::RemoveSubscriber { feed, subscriber } => {
+            try_remove_subscriber(deps, info, feed, subscriber)
+        }
+        ExecuteMsg::ConfirmPendingPrice { feed, publisher } => {
+            try_confirm_pending_price(deps, env, info, feed, publisher)
+        }
+        ExecuteMsg::DiscardPendingPrice { feed, publisher } => {
+            try_discard_pending_price(deps, info, feed, publisher)
+        }
     }
 }
 
 pub fn try_update_price(
     deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    feed: Option<FeedId>,
+    new_price: Decimal,
+) -> Result<Response, ContractError> {
+    if !PUBLISHERS.has(deps.storage, &info.sender) {
+        return Err(AccessError::Unauthorized {}.into());
+    }
+
+    let (key, feed) = load_feed(deps.storage, feed)?;
+    let submission = Submission {
+        price: new_price,
+        block_height: env.block.height,
+        block_time: env.block.time,
+    };
+    submit_price(
+        deps,
+        &env,
+        "try_update_price",
+        &info.sender,
+        &key,
+        &feed,
+        submission,
+    )
+}
+
+// Any failing entry fails the whole message, which reverts the entries before
+// it. Each feed gets its own `price_update` event.
+pub fn try_batch_update(
+    mut deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    updates: Vec<(FeedId, Decimal)>,
+) -> Result<Response, ContractError> {
+    if !PUBLISHERS.has(deps.storage, &info.sender) {
+        return Err(AccessError::Unauthorized {}.into());
+    }
+    if updates.is_empty() {
+        return Err(ContractError::EmptyBatch {});
+    }
+
+    let mut response = Response::new()
+        .add_attribute("method", "try_batch_update")
+        .add_attribute("publisher", &info.sender)
+        .add_attribute("updates", updates.len().to_string());
+    let mut seen = vec![];
+    for (feed, price) in updates {
+        let (key, feed) = load_feed(deps.storage, Some(feed))?;
+        if seen.contains(&key) {
+            return Err(ContractError::DuplicateBatchFeed {
+                feed: feed_label(&feed.id),
+            });
+        }
+
+        let submission = Submission {
+            price,
+            block_height: env.block.height,
+            block_time: env.block.time,
+        };
+        let update = submit_price(
+            deps.branch(),
+            &env,
+            "try_batch_update",
+            &info.sender,
+            &key,
+            &feed,
+            submission,
+        )?;
+        response = response
+            .add_submessages(update.messages)
+            .add_event(Event::new("price_update").add_attributes(update.attributes));
+        seen.push(key);
+    }
+    Ok(response)
+}
+
+pub fn try_submit_attestation(
+    deps: DepsMut,
+    env: Env,
+    signer: String,
+    payload: Binary,
+    signature: Binary,
+) -> Result<Response, ContractError> {
+    let signer = deps.api.addr_validate(&signer)?;
+    let Signer { public_key } =
+        SIGNERS
+            .may_load(deps.storage, &signer)?
+            .ok_or_else(|| ContractError::UnknownSigner {
+                signer: signer.to_string(),
+            })?;
+
+    let message_hash = Sha256::digest(payload.as_slice());
+    // Malformed signatures fail verification just like wrong ones
+    let verified = deps
+        .api
+        .secp256k1_verify(&message_hash, &signature, &public_key)
+        .unwrap_or(false);
+    if !verified {
+        return Err(ContractError::InvalidSignature {});
+    }
+
+    let Attestation {
+        oracle,
+        chain_id,
+        feed,
+        price,
+        timestamp,
+        nonce,
+    } = from_binary(&payload)?;
+    // Signers may be registered with other deployments too
+    if oracle != env.contract.address.as_str() || chain_id != env.block.chain_id {
+        return Err(ContractError::AttestationWrongDomain { oracle, chain_id });
+    }
+    let (key, feed) = load_feed(deps.storage, Some(feed))?;
+
+    // Anyone may relay a signed price, so each one must only ever be accepted once
+    if let Some(last) = LAST_ATTESTATIONS.may_load(deps.storage, (&key, &signer))? {
+        if (timestamp, nonce) <= (last.timestamp, last.nonce) {
+            return Err(ContractError::AttestationReplay { timestamp, nonce });
+        }
+    }
+    if timestamp > env.block.time.seconds() {
+        return Err(ContractError::AttestationFromFuture { timestamp });
+    }
+    LAST_ATTESTATIONS.save(
+        deps.storage,
+        (&key, &signer),
+        &LastAttestation { timestamp, nonce },
+    )?;
+
+    // The price is as fresh as its signature, not as the relaying transaction
+    let submission = Submission {
+        price,
+        block_height: env.block.height,
+        block_time: Timestamp::from_seconds(timestamp),
+    };
+    submit_price(
+        deps,
+        &env,
+        "try_submit_attestation",
+        &signer,
+        &key,
+        &feed,
+        submission,
+    )
+}
+
+pub fn try_pause(deps: DepsMut, info: MessageInfo) -> Result<Response, ContractError> {
+    access::assert_role(deps.storage, GUARDIAN_ROLE, &info.sender)?;
+    let mut state = STATE.load(deps.storage)?;
+
+    state.paused = true;
+    STATE.save(deps.storage, &state)?;
+
+    Ok(Response::new()
+        .add_attribute("method", "try_pause")
+        .add_attribute("paused_by", info.sender))
+}
+
+pub fn try_unpause(deps: DepsMut, info: MessageInfo) -> Result<Response, ContractError> {
+    access::assert_owner(deps.storage, &info.sender)?;
+    let mut state = STATE.load(deps.storage)?;
+
+    state.paused = false;
+    STATE.save(deps.storage, &state)?;
+
+    Ok(Response::new()
+        .add_attribute("method", "try_unpause")
+        .add_attribute("unpaused_by", info.sender))
+}
+
+pub fn try_set_bonding_config(
+    deps: DepsMut,
+    info: MessageInfo,
+    config: Option<BondingConfig>,
+) -> Result<Response, ContractError> {
+    access::assert_owner(deps.storage, &info.sender)?;
+    let mut state = STATE.load(deps.storage)?;
+
+    state.bonding = match config {
+        Some(config) => {
+            if config.strikes_to_slash == 0 || config.slash_ratio > Decimal::one() {
+                return Err(ContractError::InvalidBondingConfig {});
+            }
+            Some(Bonding {
+                min_bond: config.min_bond,
+                max_deviation: config.max_deviation,
+                strikes_to_slash: config.strikes_to_slash,
+                slash_ratio: config.slash_ratio,
+                treasury: deps.api.addr_validate(&config.treasury)?,
+                unbonding_seconds: config.unbonding_seconds,
+            })
+        }
+        None => None,
+    };
+    STATE.save(deps.storage, &state)?;
+
+    let mut response = Response::new().add_attribute("method", "try_set_bonding_config");
+    if let Some(bonding) = state.bonding {
+        response = response
+            .add_attribute("min_bond", bonding.min_bond)
+            .add_attribute("treasury", bonding.treasury);
+    }
+    Ok(response)
+}
+
+pub fn try_bond(deps: DepsMut, info: MessageInfo) -> Result<Response, ContractError> {
+    let amount = must_pay(&info, BOND_DENOM)?;
+    let mut bond = BONDS
+        .may_load(deps.storage, &info.sender)?
+        .unwrap_or_default();
+    bond.amount += amount;
+    BONDS.save(deps.storage, &info.sender, &bond)?;
+
+    Ok(Response::new()
+        .add_attribute("method", "try_bond")
+        .add_attribute("publisher", info.sender)
+        .add_attribute("amount", amount)
+        .add_attribute("bonded", bond.amount))
+}
+
+pub fn try_unbond(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    amount: Uint128,
+) -> Result<Response, ContractError> {
+    let state = STATE.load(deps.storage)?;
+    let mut bond = BONDS
+        .may_load(deps.storage, &info.sender)?
+        .unwrap_or_default();
+    if amount.is_zero() || amount > bond.amount {
+        return Err(ContractError::UnbondTooLarge {
+            bonded: bond.amount,
+        });
+    }
+
+    // Unbonding stake can still be slashed until it is released
+    let delay = state.bonding.map_or(0, |bonding| bonding.unbonding_seconds);
+    let release_at = env.block.time.plus_seconds(delay);
+    bond.amount -= amount;
+    bond.unbonding.push(Unbonding { amount, release_at });
+    BONDS.save(deps.storage, &info.sender, &bond)?;
+
+    Ok(Response::new()
+        .add_attribute("method", "try_unbond")
+        .add_attribute("publisher", info.sender)
+        .add_attribute("amount", amount)
+        .add_attribute("release_at", release_at.seconds().to_string()))
+}
+
+pub fn try_withdraw_unbonded(
+    deps: DepsMut,
+    env: Env,
     info: MessageInfo,
-    new_price: u64,
 ) -> Result<Response, ContractError> {
-    let State {
-        owner,
-        price: current_price,
-    } = STATE.load(deps.storage)?;
-    let sender_addr = deps.api.addr_validate(&info.sender.as_str())?;
+    let mut bond = BONDS
+        .may_load(deps.storage, &info.sender)?
+        .unwrap_or_default();
+    let (released, unbonding): (Vec<_>, Vec<_>) = bond
+        .unbonding
+        .into_iter()
+        .partition(|unbonding| unbonding.release_at <= env.block.time);
+    let amount = released
+        .iter()
+        .fold(Uint128::zero(), |sum, unbonding| sum + unbonding.amount);
+    if amount.is_zero() {
+        return Err(ContractError::NothingToWithdraw {});
+    }
 
-    if owner != sender_addr {
-        return Err(ContractError::Unauthorized {});
+    bond.unbonding = unbonding;
+    if bond == Bond::default() {
+        BONDS.remove(deps.storage, &info.sender);
+    } else {
+        BONDS.save(deps.storage, &info.sender, &bond)?;
     }
 
-    let state = STATE.update::<_, StdError>(deps.storage, |s| {
-        Ok(State {
-            owner: s.owner,
-            price: new_price,
+    Ok(Response::new()
+        .add_message(BankMsg::Send {
+            to_address: info.sender.to_string(),
+            amount: coins(amount.u128(), BOND_DENOM),
         })
-    })?;
+        .add_attribute("method", "try_withdraw_unbonded")
+        .add_attribute("publisher", info.sender)
+        .add_attribute("amount", amount))
+}
+
+pub fn try_add_publisher(
+    deps: DepsMut,
+    info: MessageInfo,
+    address: String,
+) -> Result<Response, ContractError> {
+    access::assert_owner(deps.storage, &info.sender)?;
+
+    let publisher = deps.api.addr_validate(&address)?;
+    PUBLISHERS.save(deps.storage, &publisher, &Empty {})?;
 
     Ok(Response::new()
-        .add_attribute("method", "try_update_price")
-        .add_attribute("owner", &state.owner)
-        .add_attribute("old_price", &current_price.to_string())
-        .add_attribute("new_price", &state.price.to_string()))
+        .add_attribute("method", "try_add_publisher")
+        .add_attribute("publisher", publisher))
 }
 
-#[cfg_attr(not(feature = "library"), entry_point)]
-pub fn query(deps: Deps, _env: Env, msg: QueryMsg) -> StdResult<Binary> {
-    match msg {
-        QueryMsg::QueryPrice {} => Ok(to_binary(&query_price(deps)?)?),
+pub fn try_remove_publisher(
+    deps: DepsMut,
+    info: MessageInfo,
+    address: String,
+) -> Result<Response, ContractError> {
+    access::assert_owner(deps.storage, &info.sender)?;
+
+    let publisher = deps.api.addr_validate(&address)?;
+    if !PUBLISHERS.has(deps.storage, &publisher) {
+        return Err(ContractError::PublisherNotFound { address });
+    }
+
+    // A removed publisher's last prices must not keep counting towards any median
+    PUBLISHERS.remove(deps.storage, &publisher);
+    let keys = FEEDS
+        .keys(deps.storage, None, None, Order::Ascending)
+        .collect::<Vec<_>>();
+    for key in keys {
+        SUBMISSIONS.remove(deps.storage, (&key, &publisher));
+        PENDING.remove(deps.storage, (&key, &publisher));
+        COMMITMENTS.remove(deps.storage, (&key, &publisher));
     }
+
+    Ok(Response::new()
+        .add_attribute("method", "try_remove_publisher")
+        .add_attribute("publisher", publisher))
 }
 
-pub fn query_price(deps: Deps) -> StdResult<PriceResponse> {
-    let State { price, .. } = STATE.load(deps.storage)?;
-    Ok(PriceResponse { price })
+pub fn try_add_signer(
+    deps: DepsMut,
+    info: MessageInfo,
+    address: String,
+    public_key: Binary,
+) -> Result<Response, ContractError> {
+    access::assert_owner(deps.storage, &info.sender)?;
+
+    // Compressed or uncompressed secp256k1 keys
+    if public_key.len() != 33 && public_key.len() != 65 {
+        return Err(ContractError::InvalidPublicKey {});
+    }
+    let signer = deps.api.addr_validate(&address)?;
+    SIGNERS.save(deps.storage, &signer, &Signer { public_key })?;
+
+    Ok(Response::new()
+        .add_attribute("method", "try_add_signer")
+        .add_attribute("signer", signer))
 }
 
-#[cfg(test)]
-mod tests {
-    use super::*;
-    use cosmwasm_std::testing::{mock_dependencies, mock_env, mock_info};
-    use cosmwasm_std::{coins, from_binary, Attribute};
+pub fn try_remove_signer(
+    deps: DepsMut,
+    info: MessageInfo,
+    address: String,
+) -> Result<Response, ContractError> {
+    access::assert_owner(deps.storage, &info.sender)?;
 
-    #[test]
-    fn proper_initialization() {
-        let mut deps = mock_dependencies(&[]);
+    let signer = deps.api.addr_validate(&address)?;
+    if !SIGNERS.has(deps.storage, &signer) {
+        return Err(ContractError::UnknownSigner { signer: address });
+    }
 
-        let msg = InstantiateMsg { price: 17 };
-        let info = mock_info("creator", &coins(1000, "earth"));
+    SIGNERS.remove(deps.storage, &signer);
+    let keys = FEEDS
+        .keys(deps.storage, None, None, Order::Ascending)
+        .collect::<Vec<_>>();
+    for key in keys {
+        SUBMISSIONS.remove(deps.storage, (&key, &signer));
+        PENDING.remove(deps.storage, (&key, &signer));
+        COMMITMENTS.remove(deps.storage, (&key, &signer));
+    }
 
-        // we can just call .unwrap() to assert this was a success
-        let res = instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
-        assert_eq!(0, res.messages.len());
+    Ok(Response::new()
+        .add_attribute("method", "try_remove_signer")
+        .add_attribute("signer", signer))
+}
 
-        // it worked, let's query the state
-        let res: PriceResponse =
-            from_binary(&query(deps.as_ref(), mock_env(), QueryMsg::QueryPrice {}).unwrap())
-                .unwrap();
-        assert_eq!(17, res.price);
+pub fn try_register_feed(
+    deps: DepsMut,
+    info: MessageInfo,
+    feed: FeedId,
+    decimals: u8,
+) -> Result<Response, ContractError> {
+    access::assert_owner(deps.storage, &info.sender)?;
+
+    if decimals > MAX_DECIMALS {
+        return Err(ContractError::InvalidDecimals { decimals });
     }
+    let feed = validate_feed_id(deps.as_ref(), feed)?;
+    let key = feed_key(&feed);
+    if FEEDS.has(deps.storage, &key) {
+        return Err(ContractError::FeedAlreadyExists {
+            feed: feed_label(&feed),
+        });
+    }
+    FEEDS.save(
+        deps.storage,
+        &key,
+        &Feed {
+            id: feed.clone(),
+            decimals,
+            history_count: 0,
+            bounds: PriceBounds::default(),
+            rounds: None,
+            round_count: 0,
+            native_reference: None,
+            ema_half_life: DEFAULT_EMA_HALF_LIFE,
+            heartbeat: DEFAULT_HEARTBEAT,
+            rate_limit: None,
+            ema: None,
+        },
+    )?;
 
-    #[test]
-    fn error_on_unauthorized_update() {
-        let mut deps = mock_dependencies(&[]);
+    Ok(Response::new()
+        .add_attribute("method", "try_register_feed")
+        .add_attribute("feed", feed_label(&feed))
+        .add_attribute("decimals", decimals.to_string()))
+}
 
-        let msg = InstantiateMsg { price: 10 };
-        let info = mock_info("creator", &[]);
+pub fn try_remove_feed(
+    deps: DepsMut,
+    info: MessageInfo,
+    feed: FeedId,
+) -> Result<Response, ContractError> {
+    access::assert_owner(deps.storage, &info.sender)?;
+    let state = STATE.load(deps.storage)?;
 
-        let _res = instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+    let (key, feed) = load_feed(deps.storage, Some(feed))?;
+    if feed.id == state.default_feed {
+        return Err(ContractError::DefaultFeedRemoval {});
+    }
 
-        // Try updating price from a non-owner address
-        let msg = ExecuteMsg::UpdatePrice { price: 58 };
-        let info = mock_info("not_creator", &[]);
+    FEEDS.remove(deps.storage, &key);
+    let slots = feed.history_count.min(state.history_size as u64);
+    for slot in 0..slots {
+        HISTORY.remove(deps.storage, (&key, U64Key::new(slot)));
+    }
+    let slots = feed.round_count.min(state.history_size as u64);
+    for slot in 0..slots {
+        ROUNDS.remove(deps.storage, (&key, U64Key::new(slot)));
+    }
+    let publishers = SUBMISSIONS
+        .prefix(&key)
+        .keys(deps.storage, None, None, Order::Ascending)
+        .collect::<Vec<_>>();
+    for publisher in publishers {
+        let publisher = Addr::unchecked(to_string(publisher)?);
+        SUBMISSIONS.remove(deps.storage, (&key, &publisher));
+    }
+    // A publisher's first price may be held without any submission
+    let pending = PENDING
+        .prefix(&key)
+        .keys(deps.storage, None, None, Order::Ascending)
+        .collect::<Vec<_>>();
+    for publisher in pending {
+        let publisher = Addr::unchecked(to_string(publisher)?);
+        PENDING.remove(deps.storage, (&key, &publisher));
+    }
+    clear_commitments(deps.storage, &key)?;
+    let subscribers = SUBSCRIBERS
+        .prefix(&key)
+        .keys(deps.storage, None, None, Order::Ascending)
+        .collect::<Vec<_>>();
+    for subscriber in subscribers {
+        let subscriber = Addr::unchecked(to_string(subscriber)?);
+        SUBSCRIBERS.remove(deps.storage, (&key, &subscriber));
+    }
 
-        // Check that Unauthorized Error is thrown
-        let err = execute(deps.as_mut(), mock_env(), info, msg).unwrap_err();
-        match err {
-            ContractError::Unauthorized {} => {}
-            _ => panic!("Expected ContractError::Unauthorized. Got something different."),
+    Ok(Response::new()
+        .add_attribute("method", "try_remove_feed")
+        .add_attribute("feed", feed_label(&feed.id)))
+}
+
+pub fn try_set_price_bounds(
+    deps: DepsMut,
+    info: MessageInfo,
+    feed: Option<FeedId>,
+    bounds: PriceBounds,
+) -> Result<Response, ContractError> {
+    access::assert_owner(deps.storage, &info.sender)?;
+
+    let (key, mut feed) = load_feed(deps.storage, feed)?;
+    feed.bounds = bounds;
+    FEEDS.save(deps.storage, &key, &feed)?;
+
+    let describe =
+        |limit: Option<Decimal>| limit.map_or_else(|| "none".to_string(), |l| l.to_string());
+    Ok(Response::new()
+        .add_attribute("method", "try_set_price_bounds")
+        .add_attribute("feed", feed_label(&feed.id))
+        .add_attribute("min_price", describe(feed.bounds.min_price))
+        .add_attribute("max_price", describe(feed.bounds.max_price))
+        .add_attribute("max_change", describe(feed.bounds.max_change))
+        .add_attribute("hold_for_review", feed.bounds.hold_for_review.to_string()))
+}
+
+pub fn try_set_ema_half_life(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    feed: Option<FeedId>,
+    half_life_seconds: u64,
+) -> Result<Response, ContractError> {
+    access::assert_owner(deps.storage, &info.sender)?;
+
+    if half_life_seconds == 0 {
+        return Err(ContractError::InvalidHalfLife {});
+    }
+    let (key, mut feed) = load_feed(deps.storage, feed)?;
+
+    // Settle the average so far under the old half-life before switching
+    if let Some(ema) = &feed.ema {
+        let spot = last_observation(deps.storage, &key, &feed)?.map_or(ema.price, |o| o.price);
+        feed.ema = Some(Ema {
+            price: ema_at(ema, spot, env.block.time, feed.ema_half_life),
+            block_time: env.block.time,
+        });
+    }
+    feed.ema_half_life = half_life_seconds;
+    FEEDS.save(deps.storage, &key, &feed)?;
+
+    Ok(Response::new()
+        .add_attribute("method", "try_set_ema_half_life")
+        .add_attribute("feed", feed_label(&feed.id))
+        .add_attribute("half_life_seconds", half_life_seconds.to_string()))
+}
+
+pub fn try_set_heartbeat(
+    deps: DepsMut,
+    info: MessageInfo,
+    feed: Option<FeedId>,
+    heartbeat_seconds: u64,
+) -> Result<Response, ContractError> {
+    access::assert_owner(deps.storage, &info.sender)?;
+
+    if heartbeat_seconds == 0 {
+        return Err(ContractError::InvalidHeartbeat {});
+    }
+    let (key, mut feed) = load_feed(deps.storage, feed)?;
+    feed.heartbeat = heartbeat_seconds;
+    FEEDS.save(deps.storage, &key, &feed)?;
+
+    Ok(Response::new()
+        .add_attribute("method", "try_set_heartbeat")
+        .add_attribute("feed", feed_label(&feed.id))
+        .add_attribute("heartbeat_seconds", heartbeat_seconds.to_string()))
+}
+
+pub fn try_set_native_reference(
+    deps: DepsMut,
+    info: MessageInfo,
+    feed: Option<FeedId>,
+    reference: Option<NativeReference>,
+) -> Result<Response, ContractError> {
+    access::assert_owner(deps.storage, &info.sender)?;
+
+    let (key, mut feed) = load_feed(deps.storage, feed)?;
+    if let Some(reference) = &reference {
+        match &reference.source {
+            NativeSource::ExchangeRate {} => {
+                if !matches!(feed.id.asset, AssetId::Native { .. }) {
+                    return Err(ContractError::InvalidNativeReference {});
+                }
+            }
+            NativeSource::Chained { via } => {
+                let (_, via) = load_feed(deps.storage, Some(via.clone()))?;
+                if via.id.asset != feed.id.asset || via.id.quote_denom == feed.id.quote_denom {
+                    return Err(ContractError::InvalidNativeReference {});
+                }
+            }
         }
+    }
+    feed.native_reference = reference;
+    FEEDS.save(deps.storage, &key, &feed)?;
 
-        // Check that price is unchanged from instantiation
-        let res: PriceResponse =
-            from_binary(&query(deps.as_ref(), mock_env(), QueryMsg::QueryPrice {}).unwrap())
-                .unwrap();
-        assert_eq!(10, res.price);
+    let mut response = Response::new()
+        .add_attribute("method", "try_set_native_reference")
+        .add_attribute("feed", feed_label(&feed.id));
+    if let Some(reference) = feed.native_reference {
+        response = response.add_attribute("tolerance", reference.tolerance.to_string());
     }
+    Ok(response)
+}
 
-    #[test]
-    fn owner_can_update() {
-        let mut deps = mock_dependencies(&[]);
+pub fn try_set_rate_limit(
+    deps: DepsMut,
+    info: MessageInfo,
+    feed: Option<FeedId>,
+    rate_limit: Option<RateLimit>,
+) -> Result<Response, ContractError> {
+    access::assert_owner(deps.storage, &info.sender)?;
 
-        let msg = InstantiateMsg { price: 10 };
-        let info = mock_info("creator", &[]);
+    if let Some(rate_limit) = &rate_limit {
+        if rate_limit.min_interval == 0 {
+            return Err(ContractError::InvalidRateLimit {});
+        }
+    }
+    let (key, mut feed) = load_feed(deps.storage, feed)?;
+    feed.rate_limit = rate_limit;
+    FEEDS.save(deps.storage, &key, &feed)?;
 
-        let _res = instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+    let mut response = Response::new()
+        .add_attribute("method", "try_set_rate_limit")
+        .add_attribute("feed", feed_label(&feed.id));
+    if let Some(rate_limit) = feed.rate_limit {
+        response = response.add_attribute("min_interval", rate_limit.min_interval.to_string());
+    }
+    Ok(response)
+}
 
-        // Try updating price from owner
-        let info = mock_info("creator", &[]);
-        let msg = ExecuteMsg::UpdatePrice { price: 58 };
-        let res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+pub fn try_set_round_config(
+    deps: DepsMut,
+    info: MessageInfo,
+    feed: Option<FeedId>,
+    config: Option<RoundConfig>,
+) -> Result<Response, ContractError> {
+    access::assert_owner(deps.storage, &info.sender)?;
 
-        // Check response attributes
-        let Attribute { key, value } = res.attributes.get(0).unwrap();
-        assert_eq!("method", key);
-        assert_eq!("try_update_price", value);
+    if let Some(config) = &config {
+        if config.commit_seconds == 0 || config.reveal_seconds == 0 {
+            return Err(ContractError::InvalidRoundConfig {});
+        }
+    }
+    let (key, mut feed) = load_feed(deps.storage, feed)?;
+    feed.rounds = config;
+    FEEDS.save(deps.storage, &key, &feed)?;
 
-        let Attribute { key, value } = res.attributes.get(1).unwrap();
-        assert_eq!("owner", key);
-        assert_eq!("creator", value);
+    let mut response = Response::new()
+        .add_attribute("method", "try_set_round_config")
+        .add_attribute("feed", feed_label(&feed.id));
+    if let Some(config) = feed.rounds {
+        response = response
+            .add_attribute("commit_seconds", config.commit_seconds.to_string())
+            .add_attribute("reveal_seconds", config.reveal_seconds.to_string());
+    }
+    Ok(response)
+}
 
-        let Attribute { key, value } = res.attributes.get(2).unwrap();
-        assert_eq!("old_price", key);
-        assert_eq!("10", value);
+pub fn try_start_round(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    feed: Option<FeedId>,
+) -> Result<Response, ContractError> {
+    if !PUBLISHERS.has(deps.storage, &info.sender) {
+        return Err(AccessError::Unauthorized {}.into());
+    }
+    ensure_not_paused(deps.storage)?;
 
-        let Attribute { key, value } = res.attributes.get(3).unwrap();
-        assert_eq!("new_price", key);
-        assert_eq!("58", value);
+    let (key, mut feed) = load_feed(deps.storage, feed)?;
+    let config = feed.rounds.clone().ok_or_else(|| ContractError::NoRounds {
+        feed: feed_label(&feed.id),
+    })?;
+    if let Some(round) = current_round(deps.storage, &key, &feed)? {
+        if !round.finalized {
+            return Err(wrong_phase(&round, &env));
+        }
+    }
 
-        // Check that price is updated to new value
-        let res: PriceResponse =
-            from_binary(&query(deps.as_ref(), mock_env(), QueryMsg::QueryPrice {}).unwrap())
-                .unwrap();
-        assert_eq!(58, res.price);
+    // Commitments left over from earlier rounds can never be revealed
+    clear_commitments(deps.storage, &key)?;
+
+    let commit_end = env.block.time.plus_seconds(config.commit_seconds);
+    let round = Round {
+        id: feed.round_count,
+        commit_end,
+        reveal_end: commit_end.plus_seconds(config.reveal_seconds),
+        price: None,
+        finalized: false,
+    };
+    let history_size = STATE.load(deps.storage)?.history_size as u64;
+    ROUNDS.save(
+        deps.storage,
+        (&key, U64Key::new(round.id % history_size)),
+        &round,
+    )?;
+    feed.round_count += 1;
+    FEEDS.save(deps.storage, &key, &feed)?;
+
+    Ok(Response::new()
+        .add_attribute("method", "try_start_round")
+        .add_attribute("feed", feed_label(&feed.id))
+        .add_attribute("round", round.id.to_string())
+        .add_attribute("commit_end", round.commit_end.seconds().to_string())
+        .add_attribute("reveal_end", round.reveal_end.seconds().to_string()))
+}
+
+pub fn try_commit_price(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    feed: Option<FeedId>,
+    commitment: Binary,
+) -> Result<Response, ContractError> {
+    if !PUBLISHERS.has(deps.storage, &info.sender) {
+        return Err(AccessError::Unauthorized {}.into());
+    }
+    ensure_not_paused(deps.storage)?;
+    ensure_bonded(deps.storage, &info.sender)?;
+
+    let (key, feed) = load_feed(deps.storage, feed)?;
+    let round = load_round_in_phase(deps.storage, &env, &key, &feed, RoundPhase::Commit)?;
+
+    // Publishers may change their commitment until the commit window closes
+    COMMITMENTS.save(
+        deps.storage,
+        (&key, &info.sender),
+        &Commitment {
+            round: round.id,
+            hash: commitment,
+            revealed: None,
+        },
+    )?;
+
+    Ok(Response::new()
+        .add_attribute("method", "try_commit_price")
+        .add_attribute("publisher", info.sender)
+        .add_attribute("feed", feed_label(&feed.id))
+        .add_attribute("round", round.id.to_string()))
+}
+
+pub fn try_reveal_price(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    feed: Option<FeedId>,
+    price: Decimal,
+    salt: String,
+) -> Result<Response, ContractError> {
+    ensure_not_paused(deps.storage)?;
+    let (key, feed) = load_feed(deps.storage, feed)?;
+    let round = load_round_in_phase(deps.storage, &env, &key, &feed, RoundPhase::Reveal)?;
+
+    let mut commitment = COMMITMENTS
+        .may_load(deps.storage, (&key, &info.sender))?
+        .filter(|c| c.round == round.id && c.revealed.is_none())
+        .ok_or_else(|| ContractError::NoCommitment {
+            publisher: info.sender.to_string(),
+        })?;
+    let preimage = format!(
+        "{}:{}:{}:{}:{}",
+        info.sender,
+        feed_label(&feed.id),
+        round.id,
+        price,
+        salt
+    );
+    let hash = Sha256::digest(preimage.as_bytes());
+    if hash.as_slice() != commitment.hash.as_slice() {
+        return Err(ContractError::CommitmentMismatch {});
+    }
+
+    // Rounds have nobody to review held prices, out-of-bound reveals are rejected
+    validate_price(price, feed.decimals)?;
+    check_native_reference(deps.as_ref(), &env, &feed, price)?;
+    let last_price = last_observation(deps.storage, &key, &feed)?.map(|o| o.price);
+    check_bounds(&feed.bounds, price, last_price)?;
+
+    commitment.revealed = Some(price);
+    COMMITMENTS.save(deps.storage, (&key, &info.sender), &commitment)?;
+
+    Ok(Response::new()
+        .add_attribute("method", "try_reveal_price")
+        .add_attribute("publisher", info.sender)
+        .add_attribute("feed", feed_label(&feed.id))
+        .add_attribute("round", round.id.to_string())
+        .add_attribute("price", price.to_string()))
+}
+
+pub fn try_finalize_round(
+    mut deps: DepsMut,
+    env: Env,
+    feed: Option<FeedId>,
+) -> Result<Response, ContractError> {
+    ensure_not_paused(deps.storage)?;
+    let (key, feed) = load_feed(deps.storage, feed)?;
+    let mut round = load_round_in_phase(deps.storage, &env, &key, &feed, RoundPhase::Ended)?;
+
+    let revealed = round_reveals(deps.storage, &key, round.id)?;
+    let mut prices = revealed.iter().map(|(_, price)| *price).collect::<Vec<_>>();
+    round.price = median(&mut prices).map(|price| truncate(price, feed.decimals));
+    round.finalized = true;
+    let history_size = STATE.load(deps.storage)?.history_size as u64;
+    ROUNDS.save(
+        deps.storage,
+        (&key, U64Key::new(round.id % history_size)),
+        &round,
+    )?;
+
+    // Revealed prices count as regular submissions made at finalization
+    let last_price = last_observation(deps.storage, &key, &feed)?.map(|o| o.price);
+    for (publisher, price) in &revealed {
+        PENDING.remove(deps.storage, (&key, publisher));
+        SUBMISSIONS.save(
+            deps.storage,
+            (&key, publisher),
+            &Submission {
+                price: *price,
+                block_height: env.block.height,
+                block_time: env.block.time,
+            },
+        )?;
+    }
+    clear_commitments(deps.storage, &key)?;
+    let mut hooks = vec![];
+    if !revealed.is_empty() {
+        record_observation(deps.branch(), &env, &key)?;
+        hooks = price_hooks(deps.as_ref(), &env, &key)?;
+    }
+
+    // Each reveal is judged like a submission, against the others' prices
+    let mut slashes = vec![];
+    for (publisher, price) in &revealed {
+        let reference = outlier_reference(deps.as_ref(), &env, &key, publisher)?.or(last_price);
+        if let Some(reference) = reference {
+            slashes.extend(strike_outlier(
+                deps.storage,
+                &env,
+                publisher,
+                *price,
+                reference,
+            )?);
+        }
+    }
+
+    Ok(Response::new()
+        .add_submessages(hooks)
+        .add_messages(slashes)
+        .add_attribute("method", "try_finalize_round")
+        .add_attribute("feed", feed_label(&feed.id))
+        .add_attribute("round", round.id.to_string())
+        .add_attribute("reveals", revealed.len().to_string())
+        .add_attribute(
+            "price",
+            round
+                .price
+                .map_or_else(|| "none".to_string(), |p| p.to_string()),
+        ))
+}
+
+pub fn try_subscribe(
+    deps: DepsMut,
+    info: MessageInfo,
+    feed: Option<FeedId>,
+) -> Result<Response, ContractError> {
+    let (key, feed) = load_feed(deps.storage, feed)?;
+    if SUBSCRIBERS.has(deps.storage, (&key, &info.sender)) {
+        return Err(ContractError::AlreadySubscribed {
+            subscriber: info.sender.to_string(),
+            feed: feed_label(&feed.id),
+        });
+    }
+    SUBSCRIBERS.save(
+        deps.storage,
+        (&key, &info.sender),
+        &Subscription { approved: false },
+    )?;
+
+    Ok(Response::new()
+        .add_attribute("method", "try_subscribe")
+        .add_attribute("subscriber", info.sender)
+        .add_attribute("feed", feed_label(&feed.id)))
+}
+
+pub fn try_unsubscribe(
+    deps: DepsMut,
+    info: MessageInfo,
+    feed: Option<FeedId>,
+) -> Result<Response, ContractError> {
+    let (key, feed) = load_feed(deps.storage, feed)?;
+    load_subscription(deps.storage, &key, &feed, &info.sender)?;
+    SUBSCRIBERS.remove(deps.storage, (&key, &info.sender));
+
+    Ok(Response::new()
+        .add_attribute("method", "try_unsubscribe")
+        .add_attribute("subscriber", info.sender)
+        .add_attribute("feed", feed_label(&feed.id)))
+}
+
+pub fn try_approve_subscriber(
+    deps: DepsMut,
+    info: MessageInfo,
+    feed: Option<FeedId>,
+    subscriber: String,
+) -> Result<Response, ContractError> {
+    access::assert_owner(deps.storage, &info.sender)?;
+
+    let (key, feed) = load_feed(deps.storage, feed)?;
+    let subscriber = deps.api.addr_validate(&subscriber)?;
+    let mut subscription = load_subscription(deps.storage, &key, &feed, &subscriber)?;
+    subscription.approved = true;
+    SUBSCRIBERS.save(deps.storage, (&key, &subscriber), &subscription)?;
+
+    Ok(Response::new()
+        .add_attribute("method", "try_approve_subscriber")
+        .add_attribute("subscriber", subscriber)
+        .add_attribute("feed", feed_label(&feed.id)))
+}
+
+pub fn try_remove_subscriber(
+    deps: DepsMut,
+    info: MessageInfo,
+    feed: Option<FeedId>,
+    subscriber: String,
+) -> Result<Response, ContractError> {
+    access::assert_owner(deps.storage, &info.sender)?;
+
+    let (key, feed) = load_feed(deps.storage, feed)?;
+    let subscriber = deps.api.addr_validate(&subscriber)?;
+    load_subscription(deps.storage, &key, &feed, &subscriber)?;
+    SUBSCRIBERS.remove(deps.storage, (&key, &subscriber));
+
+    Ok(Response::new()
+        .add_attribute("method", "try_remove_subscriber")
+        .add_attribute("subscriber", subscriber)
+        .add_attribute("feed", feed_label(&feed.id)))
+}
+
+pub fn try_confirm_pending_price(
+    mut deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    feed: Option<FeedId>,
+    publisher: String,
+) -> Result<Response, ContractError> {
+    access::assert_owner(deps.storage, &info.sender)?;
+    ensure_not_paused(deps.storage)?;
+
+    let (key, feed) = load_feed(deps.storage, feed)?;
+    let publisher = deps.api.addr_validate(&publisher)?;
+    let submission = PENDING
+        .may_load(deps.storage, (&key, &publisher))?
+        .ok_or_else(|| ContractError::NoPendingPrice {
+            publisher: publisher.to_string(),
+        })?;
+
+    PENDING.remove(deps.storage, (&key, &publisher));
+    SUBMISSIONS.save(deps.storage, (&key, &publisher), &submission)?;
+    record_observation(deps.branch(), &env, &key)?;
+    let hooks = price_hooks(deps.as_ref(), &env, &key)?;
+
+    Ok(Response::new()
+        .add_submessages(hooks)
+        .add_attribute("method", "try_confirm_pending_price")
+        .add_attribute("publisher", publisher)
+        .add_attribute("new_price", submission.price.to_string())
+        .add_attribute("feed", feed_label(&feed.id)))
+}
+
+pub fn try_discard_pending_price(
+    deps: DepsMut,
+    info: MessageInfo,
+    feed: Option<FeedId>,
+    publisher: String,
+) -> Result<Response, ContractError> {
+    access::assert_owner(deps.storage, &info.sender)?;
+
+    let (key, feed) = load_feed(deps.storage, feed)?;
+    let publisher = deps.api.addr_validate(&publisher)?;
+    if !PENDING.has(deps.storage, (&key, &publisher)) {
+        return Err(ContractError::NoPendingPrice {
+            publisher: publisher.to_string(),
+        });
+    }
+    PENDING.remove(deps.storage, (&key, &publisher));
+
+    Ok(Response::new()
+        .add_attribute("method", "try_discard_pending_price")
+        .add_attribute("publisher", publisher)
+        .add_attribute("feed", feed_label(&feed.id)))
+}
+
+// A failing subscriber only loses its own hook, the price update still goes through
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn reply(_deps: DepsMut, _env: Env, msg: Reply) -> StdResult<Response> {
+    match (msg.id, msg.result) {
+        (HOOK_REPLY_ID, ContractResult::Err(err)) => Ok(Response::new()
+            .add_attribute("method", "reply")
+            .add_attribute("hook_error", err)),
+        (id, _) => Err(StdError::generic_err(format!("Unknown reply id {}", id))),
+    }
+}
+
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn query(deps: Deps, env: Env, msg: QueryMsg) -> StdResult<Binary> {
+    match msg {
+        QueryMsg::QueryRoles {} => to_binary(&query_roles(deps)?),
+        QueryMsg::Access(msg) => access::query(deps, msg),
+        QueryMsg::QueryBondingConfig {} => to_binary(&query_bonding_config(deps)?),
+        QueryMsg::QueryBond { address } => to_binary(&query_bond(deps, address)?),
+        QueryMsg::QueryBonds { start_after, limit } => {
+            to_binary(&query_bonds(deps, start_after, limit)?)
+        }
+        QueryMsg::QuerySlashes { start_after, limit } => {
+            to_binary(&query_slashes(deps, start_after, limit)?)
+        }
+        QueryMsg::QueryPrice {
+            feed,
+            max_age_seconds,
+        } => to_binary(&query_price(deps, env, feed, max_age_seconds)?),
+        QueryMsg::QueryPriceV2 {
+            feed,
+            max_age_seconds,
+            kind,
+        } => to_binary(&query_price_v2(deps, env, feed, max_age_seconds, kind)?),
+        QueryMsg::QueryEmaPrice { feed } => to_binary(&query_ema_price(deps, env, feed)?),
+        QueryMsg::QueryDerivedPrice {
+            feed,
+            quote_denom,
+            max_age_seconds,
+        } => to_binary(&query_derived_price(
+            deps,
+            env,
+            feed,
+            quote_denom,
+            max_age_seconds,
+        )?),
+        QueryMsg::QueryPriceHistory {
+            feed,
+            start_after,
+            limit,
+        } => to_binary(&query_price_history(deps, feed, start_after, limit)?),
+        QueryMsg::QueryTwap {
+            feed,
+            window_seconds,
+        } => to_binary(&query_twap(deps, env, feed, window_seconds)?),
+        QueryMsg::QueryPendingPrices { feed } => to_binary(&query_pending_prices(deps, feed)?),
+        QueryMsg::QueryFeed { feed } => to_binary(&query_feed(deps, env, feed)?),
+        QueryMsg::QueryFeeds { start_after, limit } => {
+            to_binary(&query_feeds(deps, env, start_after, limit)?)
+        }
+        QueryMsg::QueryFeedHealth { feed } => to_binary(&query_feed_health(deps, env, feed)?),
+        QueryMsg::QueryFeedsByStaleness { start_after, limit } => {
+            to_binary(&query_feeds_by_staleness(deps, env, start_after, limit)?)
+        }
+        QueryMsg::QueryPublishers {
+            feed,
+            start_after,
+            limit,
+        } => to_binary(&query_publishers(deps, feed, start_after, limit)?),
+        QueryMsg::QueryPublisherPrice { feed, address } => {
+            to_binary(&query_publisher_price(deps, feed, address)?)
+        }
+        QueryMsg::QuerySigners { start_after, limit } => {
+            to_binary(&query_signers(deps, start_after, limit)?)
+        }
+        QueryMsg::QuerySubscribers {
+            feed,
+            start_after,
+            limit,
+        } => to_binary(&query_subscribers(deps, feed, start_after, limit)?),
+        QueryMsg::QueryRound { feed, round } => to_binary(&query_round(deps, env, feed, round)?),
+        QueryMsg::QueryFinalizedRounds {
+            feed,
+            start_after,
+            limit,
+        } => to_binary(&query_finalized_rounds(
+            deps,
+            env,
+            feed,
+            start_after,
+            limit,
+        )?),
+    }
+}
+
+pub fn query_roles(deps: Deps) -> StdResult<RolesResponse> {
+    let state = STATE.load(deps.storage)?;
+    let ownership = access::query_ownership(deps)?;
+    Ok(RolesResponse {
+        admin: ownership.owner,
+        pending_admin: ownership.pending_owner,
+        paused: state.paused,
+    })
+}
+
+pub fn query_bonding_config(deps: Deps) -> StdResult<BondingConfigResponse> {
+    let state = STATE.load(deps.storage)?;
+    Ok(BondingConfigResponse {
+        config: state.bonding,
+    })
+}
+
+pub fn query_bond(deps: Deps, address: String) -> StdResult<BondResponse> {
+    let address = deps.api.addr_validate(&address)?;
+    let bond = BONDS.may_load(deps.storage, &address)?.unwrap_or_default();
+    Ok(BondResponse {
+        address,
+        bonded: bond.amount,
+        strikes: bond.strikes,
+        unbonding: bond.unbonding,
+    })
+}
+
+pub fn query_bonds(
+    deps: Deps,
+    start_after: Option<String>,
+    limit: Option<u32>,
+) -> StdResult<BondsResponse> {
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+    let start = start_after
+        .map(|addr| deps.api.addr_validate(&addr))
+        .transpose()?
+        .map(|addr| Bound::exclusive(addr.as_str()));
+
+    let bonds = BONDS
+        .range(deps.storage, start, None, Order::Ascending)
+        .take(limit)
+        .map(|item| {
+            let (address, bond) = item?;
+            Ok(BondResponse {
+                address: Addr::unchecked(to_string(address)?),
+                bonded: bond.amount,
+                strikes: bond.strikes,
+                unbonding: bond.unbonding,
+            })
+        })
+        .collect::<StdResult<Vec<_>>>()?;
+
+    Ok(BondsResponse { bonds })
+}
+
+pub fn query_slashes(
+    deps: Deps,
+    start_after: Option<u64>,
+    limit: Option<u32>,
+) -> StdResult<SlashesResponse> {
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+    let end = start_after.map(|id| Bound::exclusive(U64Key::new(id)));
+
+    let slashes = SLASHES
+        .range(deps.storage, None, end, Order::Descending)
+        .take(limit)
+        .map(|item| item.map(|(_, slash)| slash))
+        .collect::<StdResult<Vec<_>>>()?;
+
+    Ok(SlashesResponse { slashes })
+}
+
+pub fn query_price(
+    deps: Deps,
+    env: Env,
+    feed: Option<FeedId>,
+    max_age_seconds: Option<u64>,
+) -> StdResult<PriceResponse> {
+    let PriceResponseV2 {
+        price,
+        last_updated_height,
+        last_updated_time,
+        ..
+    } = query_price_v2(deps, env, feed, max_age_seconds, None)?;
+
+    let price = u64::try_from(price.numerator() / price.denominator())
+        .map_err(|_| StdError::generic_err("Price does not fit into u64, use QueryPriceV2"))?;
+    Ok(PriceResponse {
+        price,
+        last_updated_height,
+        last_updated_time,
+    })
+}
+
+pub fn query_price_v2(
+    deps: Deps,
+    env: Env,
+    feed: Option<FeedId>,
+    max_age_seconds: Option<u64>,
+    kind: Option<PriceKind>,
+) -> StdResult<PriceResponseV2> {
+    check_not_paused(deps.storage)?;
+    let (key, feed) = load_feed(deps.storage, feed)?;
+    let mut price = aggregate_price(deps, &env, &key, &feed)?
+        .ok_or_else(|| StdError::generic_err("No fresh price submissions"))?;
+
+    if let Some(max_age) = max_age_seconds {
+        let age = env
+            .block
+            .time
+            .seconds()
+            .saturating_sub(price.last_updated_time.seconds());
+        if age > max_age {
+            return Err(StdError::generic_err(format!(
+                "Price is stale: last updated {} seconds ago, max age is {} seconds",
+                age, max_age
+            )));
+        }
+    }
+
+    let spot = price.price;
+    let ema = || {
+        let ema = feed.ema.as_ref().map_or(spot, |ema| {
+            ema_at(ema, spot, env.block.time, feed.ema_half_life)
+        });
+        truncate(ema, feed.decimals)
+    };
+    price.price = match kind.unwrap_or(PriceKind::Spot) {
+        PriceKind::Spot => spot,
+        PriceKind::Ema => ema(),
+        PriceKind::Min => spot.min(ema()),
+        PriceKind::Max => spot.max(ema()),
+    };
+    Ok(price)
+}
+
+pub fn query_ema_price(deps: Deps, env: Env, feed: Option<FeedId>) -> StdResult<EmaResponse> {
+    let (_, Feed { ema_half_life, .. }) = load_feed(deps.storage, feed.clone())?;
+    let spot = query_price_v2(deps, env.clone(), feed.clone(), None, None)?;
+    let ema = query_price_v2(deps, env, feed, None, Some(PriceKind::Ema))?;
+    Ok(EmaResponse {
+        ema: ema.price,
+        spot: spot.price,
+        quote_denom: spot.quote_denom,
+        half_life_seconds: ema_half_life,
+    })
+}
+
+pub fn query_derived_price(
+    deps: Deps,
+    env: Env,
+    feed: Option<FeedId>,
+    quote_denom: String,
+    max_age_seconds: Option<u64>,
+) -> StdResult<PriceResponseV2> {
+    let price = query_price_v2(deps, env, feed, max_age_seconds, None)?;
+    let rate = native_rate(deps, &price.quote_denom, &quote_denom)?;
+    Ok(PriceResponseV2 {
+        price: truncate(multiply(price.price, rate), price.decimals),
+        quote_denom,
+        ..price
+    })
+}
+
+pub fn query_price_history(
+    deps: Deps,
+    feed: Option<FeedId>,
+    start_after: Option<u64>,
+    limit: Option<u32>,
+) -> StdResult<PriceHistoryResponse> {
+    let (key, feed) = load_feed(deps.storage, feed)?;
+    let history_size = STATE.load(deps.storage)?.history_size as u64;
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+
+    // Only the last `history_size` sequence numbers are still in the buffer
+    let oldest = feed.history_count.saturating_sub(history_size);
+    let end = start_after.map_or(feed.history_count, |seq| seq.min(feed.history_count));
+
+    let observations = (oldest..end)
+        .rev()
+        .take(limit)
+        .map(|seq| HISTORY.load(deps.storage, (&key, U64Key::new(seq % history_size))))
+        .collect::<StdResult<Vec<_>>>()?;
+
+    Ok(PriceHistoryResponse { observations })
+}
+
+pub fn query_twap(
+    deps: Deps,
+    env: Env,
+    feed: Option<FeedId>,
+    window_seconds: u64,
+) -> StdResult<TwapResponse> {
+    if window_seconds == 0 {
+        return Err(StdError::generic_err("TWAP window must be positive"));
+    }
+    check_not_paused(deps.storage)?;
+
+    let (key, feed) = load_feed(deps.storage, feed)?;
+    let history_size = STATE.load(deps.storage)?.history_size as u64;
+    let now = env.block.time.seconds();
+    let window_start = now.saturating_sub(window_seconds);
+
+    // Walk back from the newest observation. Each price holds until the next
+    // observation, the oldest one in range also covers the start of the window.
+    let mut weighted_sum = Uint256::zero();
+    let mut covered_seconds = 0u64;
+    let mut period_end = now;
+    let mut latest: Option<Decimal> = None;
+    let oldest = feed.history_count.saturating_sub(history_size);
+    for seq in (oldest..feed.history_count).rev() {
+        let observation = HISTORY.load(deps.storage, (&key, U64Key::new(seq % history_size)))?;
+        latest.get_or_insert(observation.price);
+
+        let period_start = observation.block_time.seconds().max(window_start);
+        let duration = period_end.saturating_sub(period_start);
+        weighted_sum += Uint256::from(observation.price.numerator()) * Uint256::from(duration);
+        covered_seconds += duration;
+        period_end = period_start;
+
+        if observation.block_time.seconds() <= window_start {
+            break;
+        }
+    }
+
+    let price = match (latest, covered_seconds) {
+        (None, _) => return Err(StdError::generic_err("No price history")),
+        // Only an observation from this very block, nothing to average yet
+        (Some(latest), 0) => latest,
+        (Some(_), _) => {
+            let average = weighted_sum / Uint256::from(covered_seconds);
+            Decimal::from_ratio(Uint128::try_from(average)?, Decimal::one().denominator())
+        }
+    };
+
+    Ok(TwapResponse {
+        price: truncate(price, feed.decimals),
+        quote_denom: feed.id.quote_denom,
+        window_seconds,
+    })
+}
+
+pub fn query_pending_prices(deps: Deps, feed: Option<FeedId>) -> StdResult<PendingPricesResponse> {
+    let (key, _) = load_feed(deps.storage, feed)?;
+    let pending = PENDING
+        .prefix(&key)
+        .range(deps.storage, None, None, Order::Ascending)
+        .map(|item| {
+            let (publisher, submission) = item?;
+            Ok(PendingPriceResponse {
+                publisher: Addr::unchecked(to_string(publisher)?),
+                submission,
+            })
+        })
+        .collect::<StdResult<Vec<_>>>()?;
+
+    Ok(PendingPricesResponse { pending })
+}
+
+pub fn query_feed(deps: Deps, env: Env, feed: FeedId) -> StdResult<FeedResponse> {
+    check_not_paused(deps.storage)?;
+    let (key, feed) = load_feed(deps.storage, Some(feed))?;
+    Ok(FeedResponse {
+        price: aggregate_price(deps, &env, &key, &feed)?,
+        id: feed.id,
+        decimals: feed.decimals,
+        bounds: feed.bounds,
+        rounds: feed.rounds,
+        native_reference: feed.native_reference,
+        ema_half_life: feed.ema_half_life,
+        heartbeat: feed.heartbeat,
+        rate_limit: feed.rate_limit,
+    })
+}
+
+pub fn query_feeds(
+    deps: Deps,
+    env: Env,
+    start_after: Option<FeedId>,
+    limit: Option<u32>,
+) -> StdResult<FeedsResponse> {
+    check_not_paused(deps.storage)?;
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+    let start = start_after.map(|feed| Bound::exclusive(feed_key(&feed)));
+
+    let feeds = FEEDS
+        .range(deps.storage, start, None, Order::Ascending)
+        .take(limit)
+        .map(|item| {
+            let (key, feed) = item?;
+            Ok(FeedResponse {
+                price: aggregate_price(deps, &env, &key, &feed)?,
+                id: feed.id,
+                decimals: feed.decimals,
+                bounds: feed.bounds,
+                rounds: feed.rounds,
+                native_reference: feed.native_reference,
+                ema_half_life: feed.ema_half_life,
+                heartbeat: feed.heartbeat,
+                rate_limit: feed.rate_limit,
+            })
+        })
+        .collect::<StdResult<Vec<_>>>()?;
+
+    Ok(FeedsResponse { feeds })
+}
+
+pub fn query_feed_health(
+    deps: Deps,
+    env: Env,
+    feed: Option<FeedId>,
+) -> StdResult<FeedHealthResponse> {
+    let (key, feed) = load_feed(deps.storage, feed)?;
+    feed_health(deps, &env, &key, feed)
+}
+
+// Health has to be computed for every feed to sort them, so this reads all
+// feeds no matter the limit
+pub fn query_feeds_by_staleness(
+    deps: Deps,
+    env: Env,
+    start_after: Option<FeedId>,
+    limit: Option<u32>,
+) -> StdResult<FeedHealthsResponse> {
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+
+    let mut feeds = FEEDS
+        .range(deps.storage, None, None, Order::Ascending)
+        .map(|item| {
+            let (key, feed) = item?;
+            feed_health(deps, &env, &key, feed)
+        })
+        .collect::<StdResult<Vec<_>>>()?;
+    // Never updated feeds first, then by how long they are overdue
+    feeds.sort_by_key(|health| {
+        (
+            health.last_updated_time.is_some(),
+            Reverse(health.seconds_overdue),
+            feed_key(&health.feed),
+        )
+    });
+
+    let start = start_after
+        .and_then(|after| feeds.iter().position(|health| health.feed == after))
+        .map_or(0, |index| index + 1);
+    let feeds = feeds.into_iter().skip(start).take(limit).collect();
+
+    Ok(FeedHealthsResponse { feeds })
+}
+
+pub fn query_publishers(
+    deps: Deps,
+    feed: Option<FeedId>,
+    start_after: Option<String>,
+    limit: Option<u32>,
+) -> StdResult<PublishersResponse> {
+    check_not_paused(deps.storage)?;
+    let (key, _) = load_feed(deps.storage, feed)?;
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+    let start = start_after
+        .map(|addr| deps.api.addr_validate(&addr))
+        .transpose()?
+        .map(|addr| Bound::exclusive(addr.as_str()));
+
+    let publishers = PUBLISHERS
+        .keys(deps.storage, start, None, Order::Ascending)
+        .take(limit)
+        .map(|publisher| {
+            let address = Addr::unchecked(to_string(publisher)?);
+            let last_submission = SUBMISSIONS.may_load(deps.storage, (&key, &address))?;
+            Ok(PublisherResponse {
+                address,
+                last_submission,
+            })
+        })
+        .collect::<StdResult<Vec<_>>>()?;
+
+    Ok(PublishersResponse { publishers })
+}
+
+pub fn query_publisher_price(
+    deps: Deps,
+    feed: Option<FeedId>,
+    address: String,
+) -> StdResult<PublisherResponse> {
+    check_not_paused(deps.storage)?;
+    let (key, _) = load_feed(deps.storage, feed)?;
+    let address = deps.api.addr_validate(&address)?;
+    // Signers submit through attestations and are reported the same way
+    if !PUBLISHERS.has(deps.storage, &address) && !SIGNERS.has(deps.storage, &address) {
+        return Err(StdError::not_found("publisher"));
+    }
+
+    let last_submission = SUBMISSIONS.may_load(deps.storage, (&key, &address))?;
+    Ok(PublisherResponse {
+        address,
+        last_submission,
+    })
+}
+
+pub fn query_signers(
+    deps: Deps,
+    start_after: Option<String>,
+    limit: Option<u32>,
+) -> StdResult<SignersResponse> {
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+    let start = start_after
+        .map(|addr| deps.api.addr_validate(&addr))
+        .transpose()?
+        .map(|addr| Bound::exclusive(addr.as_str()));
+
+    let signers = SIGNERS
+        .range(deps.storage, start, None, Order::Ascending)
+        .take(limit)
+        .map(|item| {
+            let (address, signer) = item?;
+            Ok(SignerResponse {
+                address: Addr::unchecked(to_string(address)?),
+                public_key: signer.public_key,
+            })
+        })
+        .collect::<StdResult<Vec<_>>>()?;
+
+    Ok(SignersResponse { signers })
+}
+
+pub fn query_subscribers(
+    deps: Deps,
+    feed: Option<FeedId>,
+    start_after: Option<String>,
+    limit: Option<u32>,
+) -> StdResult<SubscribersResponse> {
+    let (key, _) = load_feed(deps.storage, feed)?;
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+    let start = start_after
+        .map(|addr| deps.api.addr_validate(&addr))
+        .transpose()?
+        .map(|addr| Bound::exclusive(addr.as_str()));
+
+    let subscribers = SUBSCRIBERS
+        .prefix(&key)
+        .range(deps.storage, start, None, Order::Ascending)
+        .take(limit)
+        .map(|item| {
+            let (address, subscription) = item?;
+            Ok(SubscriberResponse {
+                address: Addr::unchecked(to_string(address)?),
+                approved: subscription.approved,
+            })
+        })
+        .collect::<StdResult<Vec<_>>>()?;
+
+    Ok(SubscribersResponse { subscribers })
+}
+
+pub fn query_round(
+    deps: Deps,
+    env: Env,
+    feed: Option<FeedId>,
+    round: Option<u64>,
+) -> StdResult<RoundResponse> {
+    let (key, feed) = load_feed(deps.storage, feed)?;
+    let history_size = STATE.load(deps.storage)?.history_size as u64;
+    let id = match round {
+        Some(id) => id,
+        None => feed
+            .round_count
+            .checked_sub(1)
+            .ok_or_else(|| StdError::not_found("round"))?,
+    };
+
+    // Only the last `history_size` rounds are still in the buffer
+    if id >= feed.round_count || id < feed.round_count.saturating_sub(history_size) {
+        return Err(StdError::not_found("round"));
+    }
+    let round = ROUNDS.load(deps.storage, (&key, U64Key::new(id % history_size)))?;
+    round_response(deps.storage, &env, &key, round)
+}
+
+pub fn query_finalized_rounds(
+    deps: Deps,
+    env: Env,
+    feed: Option<FeedId>,
+    start_after: Option<u64>,
+    limit: Option<u32>,
+) -> StdResult<RoundsResponse> {
+    let (key, feed) = load_feed(deps.storage, feed)?;
+    let history_size = STATE.load(deps.storage)?.history_size as u64;
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+
+    let oldest = feed.round_count.saturating_sub(history_size);
+    let end = start_after.map_or(feed.round_count, |id| id.min(feed.round_count));
+
+    let rounds = (oldest..end)
+        .rev()
+        .map(|id| ROUNDS.load(deps.storage, (&key, U64Key::new(id % history_size))))
+        .filter(|round| round.as_ref().map_or(true, |round| round.finalized))
+        .take(limit)
+        .map(|round| round_response(deps.storage, &env, &key, round?))
+        .collect::<StdResult<Vec<_>>>()?;
+
+    Ok(RoundsResponse { rounds })
+}
+
+// Median of the fresh submissions for a feed, None when there are none
+fn aggregate_price(
+    deps: Deps,
+    env: &Env,
+    key: &[u8],
+    feed: &Feed,
+) -> StdResult<Option<PriceResponseV2>> {
+    let State { max_price_age, .. } = STATE.load(deps.storage)?;
+    let now = env.block.time.seconds();
+
+    let fresh = SUBMISSIONS
+        .prefix(key)
+        .range(deps.storage, None, None, Order::Ascending)
+        .map(|item| item.map(|(_, submission)| submission))
+        .collect::<StdResult<Vec<Submission>>>()?
+        .into_iter()
+        .filter(|s| now.saturating_sub(s.block_time.seconds()) <= max_price_age)
+        .collect::<Vec<Submission>>();
+
+    let latest = match fresh.iter().max_by_key(|s| s.block_time) {
+        Some(latest) => latest,
+        None => return Ok(None),
+    };
+
+    let mut prices = fresh.iter().map(|s| s.price).collect::<Vec<Decimal>>();
+    let price = median(&mut prices).unwrap_or_default();
+    let mut deviations = prices
+        .iter()
+        .map(|&p| if p > price { p - price } else { price - p })
+        .collect::<Vec<Decimal>>();
+    let confidence = median(&mut deviations).unwrap_or_default();
+    Ok(Some(PriceResponseV2 {
+        price: truncate(price, feed.decimals),
+        decimals: feed.decimals,
+        quote_denom: feed.id.quote_denom.clone(),
+        confidence: truncate(confidence, feed.decimals),
+        publishers: fresh.len() as u32,
+        last_updated_height: latest.block_height,
+        last_updated_time: latest.block_time,
+    }))
+}
+
+// Median of the other publishers' fresh prices, leaving out the submitter so
+// an outlier can't pull its own reference towards itself
+fn outlier_reference(
+    deps: Deps,
+    env: &Env,
+    key: &[u8],
+    publisher: &Addr,
+) -> StdResult<Option<Decimal>> {
+    let State { max_price_age, .. } = STATE.load(deps.storage)?;
+    let now = env.block.time.seconds();
+
+    let mut prices = vec![];
+    for item in SUBMISSIONS
+        .prefix(key)
+        .range(deps.storage, None, None, Order::Ascending)
+    {
+        let (other, submission) = item?;
+        if other != publisher.as_bytes()
+            && now.saturating_sub(submission.block_time.seconds()) <= max_price_age
+        {
+            prices.push(submission.price);
+        }
+    }
+    Ok(median(&mut prices))
+}
+
+// Checks a submission against its feed and stores it, or holds it for review
+// when it trips the circuit breaker
+fn submit_price(
+    mut deps: DepsMut,
+    env: &Env,
+    method: &str,
+    publisher: &Addr,
+    key: &[u8],
+    feed: &Feed,
+    submission: Submission,
+) -> Result<Response, ContractError> {
+    ensure_not_paused(deps.storage)?;
+    // Prices sent in the clear could be copied by the other publishers
+    if feed.rounds.is_some() {
+        return Err(ContractError::RoundBasedFeed {
+            feed: feed_label(&feed.id),
+        });
+    }
+    ensure_bonded(deps.storage, publisher)?;
+    validate_price(submission.price, feed.decimals)?;
+    check_rate_limit(deps.storage, env, key, feed, publisher, submission.price)?;
+    check_native_reference(deps.as_ref(), env, feed, submission.price)?;
+
+    let last_price = last_observation(deps.storage, key, feed)?.map(|o| o.price);
+    if let Err(breach) = check_bounds(&feed.bounds, submission.price, last_price) {
+        if !feed.bounds.hold_for_review {
+            return Err(breach);
+        }
+
+        PENDING.save(deps.storage, (key, publisher), &submission)?;
+        return Ok(Response::new()
+            .add_attribute("method", method)
+            .add_attribute("publisher", publisher)
+            .add_attribute("pending_price", submission.price.to_string())
+            .add_attribute("feed", feed_label(&feed.id))
+            .add_attribute("reason", breach.to_string()));
+    }
+
+    let old_price = SUBMISSIONS
+        .may_load(deps.storage, (key, publisher))?
+        .map_or_else(|| "none".to_string(), |s| s.price.to_string());
+
+    // A fresh price within bounds supersedes anything still held for review
+    PENDING.remove(deps.storage, (key, publisher));
+    SUBMISSIONS.save(deps.storage, (key, publisher), &submission)?;
+    record_observation(deps.branch(), env, key)?;
+    let hooks = price_hooks(deps.as_ref(), env, key)?;
+
+    // Without fresh prices from others, the last observation before this one
+    let reference = outlier_reference(deps.as_ref(), env, key, publisher)?.or(last_price);
+    let slash = match reference {
+        Some(reference) => {
+            strike_outlier(deps.storage, env, publisher, submission.price, reference)?
+        }
+        None => None,
+    };
+
+    Ok(Response::new()
+        .add_submessages(hooks)
+        .add_messages(slash)
+        .add_attribute("method", method)
+        .add_attribute("publisher", publisher)
+        .add_attribute("old_price", old_price)
+        .add_attribute("new_price", submission.price.to_string())
+        .add_attribute("feed", feed_label(&feed.id)))
+}
+
+// Hooks notifying the approved subscribers of a feed of its current price
+fn price_hooks(deps: Deps, env: &Env, key: &[u8]) -> StdResult<Vec<SubMsg>> {
+    let feed = FEEDS.load(deps.storage, key)?;
+    let price = match aggregate_price(deps, env, key, &feed)? {
+        Some(price) => price,
+        None => return Ok(vec![]),
+    };
+    let msg = to_binary(&HookMsg::PriceUpdated {
+        feed: feed.id,
+        price,
+    })?;
+
+    let mut hooks = vec![];
+    for item in SUBSCRIBERS
+        .prefix(key)
+        .range(deps.storage, None, None, Order::Ascending)
+    {
+        let (subscriber, subscription) = item?;
+        if !subscription.approved {
+            continue;
+        }
+        let execute = WasmMsg::Execute {
+            contract_addr: to_string(subscriber)?,
+            msg: msg.clone(),
+            funds: vec![],
+        };
+        hooks.push(SubMsg::reply_on_error(execute, HOOK_REPLY_ID).with_gas_limit(HOOK_GAS_LIMIT));
+    }
+    Ok(hooks)
+}
+
+fn load_subscription(
+    storage: &dyn Storage,
+    key: &[u8],
+    feed: &Feed,
+    subscriber: &Addr,
+) -> Result<Subscription, ContractError> {
+    SUBSCRIBERS
+        .may_load(storage, (key, subscriber))?
+        .ok_or_else(|| ContractError::SubscriptionNotFound {
+            subscriber: subscriber.to_string(),
+            feed: feed_label(&feed.id),
+        })
+}
+
+fn current_round(storage: &dyn Storage, key: &[u8], feed: &Feed) -> StdResult<Option<Round>> {
+    if feed.round_count == 0 {
+        return Ok(None);
+    }
+    let history_size = STATE.load(storage)?.history_size as u64;
+    let slot = (feed.round_count - 1) % history_size;
+    ROUNDS.may_load(storage, (key, U64Key::new(slot)))
+}
+
+// Loads the current round of a feed, which has to be in the given phase
+fn load_round_in_phase(
+    storage: &dyn Storage,
+    env: &Env,
+    key: &[u8],
+    feed: &Feed,
+    phase: RoundPhase,
+) -> Result<Round, ContractError> {
+    if feed.rounds.is_none() {
+        return Err(ContractError::NoRounds {
+            feed: feed_label(&feed.id),
+        });
+    }
+    let round = current_round(storage, key, feed)?.ok_or_else(|| StdError::not_found("round"))?;
+    if round_phase(&round, env) != phase {
+        return Err(wrong_phase(&round, env));
+    }
+    Ok(round)
+}
+
+fn round_phase(round: &Round, env: &Env) -> RoundPhase {
+    if round.finalized {
+        RoundPhase::Finalized
+    } else if env.block.time < round.commit_end {
+        RoundPhase::Commit
+    } else if env.block.time < round.reveal_end {
+        RoundPhase::Reveal
+    } else {
+        RoundPhase::Ended
+    }
+}
+
+fn wrong_phase(round: &Round, env: &Env) -> ContractError {
+    let phase = match round_phase(round, env) {
+        RoundPhase::Commit => "commit",
+        RoundPhase::Reveal => "reveal",
+        RoundPhase::Ended => "ended",
+        RoundPhase::Finalized => "finalized",
+    };
+    ContractError::WrongRoundPhase {
+        round: round.id,
+        phase: phase.to_string(),
+    }
+}
+
+fn round_response(
+    storage: &dyn Storage,
+    env: &Env,
+    key: &[u8],
+    round: Round,
+) -> StdResult<RoundResponse> {
+    // Commitments are cleared on finalization, so only open rounds count them
+    let reveals = if round.finalized {
+        0
+    } else {
+        round_reveals(storage, key, round.id)?.len() as u32
+    };
+    Ok(RoundResponse {
+        phase: round_phase(&round, env),
+        round,
+        reveals,
+    })
+}
+
+// Prices revealed in a round, by publisher
+fn round_reveals(storage: &dyn Storage, key: &[u8], round: u64) -> StdResult<Vec<(Addr, Decimal)>> {
+    let mut reveals = vec![];
+    for item in COMMITMENTS
+        .prefix(key)
+        .range(storage, None, None, Order::Ascending)
+    {
+        let (publisher, commitment) = item?;
+        if let (true, Some(price)) = (commitment.round == round, commitment.revealed) {
+            reveals.push((Addr::unchecked(to_string(publisher)?), price));
+        }
+    }
+    Ok(reveals)
+}
+
+fn clear_commitments(storage: &mut dyn Storage, key: &[u8]) -> StdResult<()> {
+    let publishers = COMMITMENTS
+        .prefix(key)
+        .keys(storage, None, None, Order::Ascending)
+        .collect::<Vec<_>>();
+    for publisher in publishers {
+        let publisher = Addr::unchecked(to_string(publisher)?);
+        COMMITMENTS.remove(storage, (key, &publisher));
+    }
+    Ok(())
+}
+
+// Appends the current aggregated price of a feed to its history
+fn record_observation(deps: DepsMut, env: &Env, key: &[u8]) -> StdResult<()> {
+    let history_size = STATE.load(deps.storage)?.history_size as u64;
+    let mut feed = FEEDS.load(deps.storage, key)?;
+    let price = match aggregate_price(deps.as_ref(), env, key, &feed)? {
+        Some(price) => price.price,
+        None => return Ok(()),
+    };
+
+    // The previous spot price held since the last update, fold it into the EMA
+    let ema = match (&feed.ema, last_observation(deps.storage, key, &feed)?) {
+        (Some(ema), Some(last)) => ema_at(ema, last.price, env.block.time, feed.ema_half_life),
+        _ => price,
+    };
+    feed.ema = Some(Ema {
+        price: ema,
+        block_time: env.block.time,
+    });
+
+    let slot = feed.history_count % history_size;
+    HISTORY.save(
+        deps.storage,
+        (key, U64Key::new(slot)),
+        &Observation {
+            sequence: feed.history_count,
+            price,
+            block_height: env.block.height,
+            block_time: env.block.time,
+        },
+    )?;
+
+    feed.history_count += 1;
+    FEEDS.save(deps.storage, key, &feed)
+}
+
+fn feed_health(deps: Deps, env: &Env, key: &[u8], feed: Feed) -> StdResult<FeedHealthResponse> {
+    let active_publishers = aggregate_price(deps, env, key, &feed)?.map_or(0, |p| p.publishers);
+    let last_updated_time = last_observation(deps.storage, key, &feed)?.map(|o| o.block_time);
+    let seconds_overdue = last_updated_time.map_or(0, |time| {
+        let age = env.block.time.seconds().saturating_sub(time.seconds());
+        age.saturating_sub(feed.heartbeat)
+    });
+
+    let status = if active_publishers == 0 {
+        FeedStatus::Stale
+    } else if seconds_overdue > 0 {
+        FeedStatus::Degraded
+    } else {
+        FeedStatus::Healthy
+    };
+
+    Ok(FeedHealthResponse {
+        feed: feed.id,
+        status,
+        heartbeat: feed.heartbeat,
+        last_updated_time,
+        seconds_overdue,
+        active_publishers,
+    })
+}
+
+fn last_observation(
+    storage: &dyn Storage,
+    key: &[u8],
+    feed: &Feed,
+) -> StdResult<Option<Observation>> {
+    if feed.history_count == 0 {
+        return Ok(None);
+    }
+
+    let history_size = STATE.load(storage)?.history_size as u64;
+    let slot = (feed.history_count - 1) % history_size;
+    HISTORY.may_load(storage, (key, U64Key::new(slot)))
+}
+
+// Rejects updates coming sooner than the feed's rate limit allows, unless the
+// price moved far enough from the publisher's previous one
+fn check_rate_limit(
+    storage: &dyn Storage,
+    env: &Env,
+    key: &[u8],
+    feed: &Feed,
+    publisher: &Addr,
+    price: Decimal,
+) -> Result<(), ContractError> {
+    let rate_limit = match &feed.rate_limit {
+        Some(rate_limit) => rate_limit,
+        None => return Ok(()),
+    };
+    let last = match SUBMISSIONS.may_load(storage, (key, publisher))? {
+        Some(last) => last,
+        None => return Ok(()),
+    };
+
+    let elapsed = env
+        .block
+        .time
+        .seconds()
+        .saturating_sub(last.block_time.seconds());
+    if elapsed >= rate_limit.min_interval {
+        return Ok(());
+    }
+    if let Some(deviation) = rate_limit.override_deviation {
+        if deviates(price, last.price, deviation) {
+            return Ok(());
+        }
+    }
+    Err(ContractError::UpdateTooSoon {
+        seconds_left: rate_limit.min_interval - elapsed,
+    })
+}
+
+// Rejects prices too far from what Terra's native oracle implies for the feed
+fn check_native_reference(
+    deps: Deps,
+    env: &Env,
+    feed: &Feed,
+    price: Decimal,
+) -> Result<(), ContractError> {
+    let NativeReference { source, tolerance } = match &feed.native_reference {
+        Some(reference) => reference,
+        None => return Ok(()),
+    };
+    let reference = match source {
+        NativeSource::ExchangeRate {} => match &feed.id.asset {
+            AssetId::Native { denom } => native_rate(deps, denom, &feed.id.quote_denom)?,
+            AssetId::Cw20 { .. } => return Err(ContractError::InvalidNativeReference {}),
+        },
+        NativeSource::Chained { via } => {
+            let (key, via) = load_feed(deps.storage, Some(via.clone()))?;
+            let via_price = aggregate_price(deps, env, &key, &via)?
+                .ok_or_else(|| StdError::generic_err("No fresh price submissions"))?;
+            let rate = native_rate(deps, &via.id.quote_denom, &feed.id.quote_denom)?;
+            multiply(via_price.price, rate)
+        }
+    };
+
+    if deviates(price, reference, *tolerance) {
+        return Err(ContractError::NativeReferenceMismatch {
+            price,
+            reference,
+            tolerance: *tolerance,
+        });
+    }
+    Ok(())
+}
+
+// Amount of `quote_denom` one unit of `base_denom` is worth on Terra's oracle
+fn native_rate(deps: Deps, base_denom: &str, quote_denom: &str) -> StdResult<Decimal> {
+    if base_denom == quote_denom {
+        return Ok(Decimal::one());
+    }
+    let querier = TerraQuerier::new(&deps.querier);
+    let res = querier.query_exchange_rates(base_denom, vec![quote_denom])?;
+    res.exchange_rates
+        .into_iter()
+        .find(|item| item.quote_denom == quote_denom)
+        .map(|item| item.exchange_rate)
+        .ok_or_else(|| {
+            StdError::not_found(format!(
+                "exchange rate of {} in {}",
+                base_denom, quote_denom
+            ))
+        })
+}
+
+// Moves the EMA towards `spot` for the time elapsed since it was last updated
+fn ema_at(ema: &Ema, spot: Decimal, now: Timestamp, half_life: u64) -> Decimal {
+    let elapsed = now.seconds().saturating_sub(ema.block_time.seconds());
+    let weight = half_life_decay(elapsed, half_life);
+    multiply(ema.price, weight) + multiply(spot, Decimal::one() - weight)
+}
+
+// 0.5^(elapsed / half_life), computed as 0.5^whole * e^-(ln2 * fraction) with
+// integer math only
+fn half_life_decay(elapsed: u64, half_life: u64) -> Decimal {
+    const ONE: u128 = 1_000_000_000_000_000_000;
+    let halvings = elapsed / half_life;
+    if halvings >= 64 {
+        return Decimal::zero();
+    }
+
+    // x = ln2 * remainder / half_life < ln2, so the series for e^x converges fast
+    let x = LN_2 * (elapsed % half_life) as u128 / half_life as u128;
+    let (mut term, mut exp) = (ONE, ONE);
+    for n in 1..=30u128 {
+        term = term * x / (n * ONE);
+        if term == 0 {
+            break;
+        }
+        exp += term;
+    }
+
+    Decimal::from_ratio(ONE >> halvings, exp)
+}
+
+fn multiply(a: Decimal, b: Decimal) -> Decimal {
+    let numerator = Uint128::from(a.numerator()).multiply_ratio(b.numerator(), b.denominator());
+    Decimal::from_ratio(numerator, a.denominator())
+}
+
+// Checks a price against the circuit breaker of its feed
+fn check_bounds(
+    bounds: &PriceBounds,
+    price: Decimal,
+    last_price: Option<Decimal>,
+) -> Result<(), ContractError> {
+    if let Some(min_price) = bounds.min_price {
+        if price < min_price {
+            return Err(ContractError::PriceBelowMinimum { price, min_price });
+        }
+    }
+    if let Some(max_price) = bounds.max_price {
+        if price > max_price {
+            return Err(ContractError::PriceAboveMaximum { price, max_price });
+        }
+    }
+    if let (Some(max_change), Some(last_price)) = (bounds.max_change, last_price) {
+        if deviates(price, last_price, max_change) {
+            return Err(ContractError::PriceDeviation {
+                price,
+                last_price,
+                max_change,
+            });
+        }
+    }
+    Ok(())
+}
+
+// |price - reference| / reference > max_change, kept in integers
+fn deviates(price: Decimal, reference: Decimal, max_change: Decimal) -> bool {
+    let change = if price > reference {
+        price - reference
+    } else {
+        reference - price
+    };
+    let moved = Uint256::from(change.numerator()) * Uint256::from(max_change.denominator());
+    let allowed = Uint256::from(reference.numerator()) * Uint256::from(max_change.numerator());
+    moved > allowed
+}
+
+// Median of the given prices; the mean of the two middle values for an even count
+fn median(prices: &mut [Decimal]) -> Option<Decimal> {
+    if prices.is_empty() {
+        return None;
+    }
+
+    prices.sort_unstable();
+    let mid = prices.len() / 2;
+    if prices.len() % 2 == 1 {
+        Some(prices[mid])
+    } else {
+        let (low, high) = (prices[mid - 1], prices[mid]);
+        Some(low + (high - low) / Uint128::new(2))
+    }
+}
+
+// Rounds a price down to the given number of fractional digits
+fn truncate(price: Decimal, decimals: u8) -> Decimal {
+    let unit = 10u128.pow((MAX_DECIMALS - decimals) as u32);
+    let numerator = price.numerator();
+    Decimal::from_ratio(numerator - numerator % unit, price.denominator())
+}
+
+fn ensure_bonded(storage: &dyn Storage, publisher: &Addr) -> Result<(), ContractError> {
+    let min_bond = match STATE.load(storage)?.bonding {
+        Some(bonding) => bonding.min_bond,
+        None => return Ok(()),
+    };
+    let bonded = BONDS
+        .may_load(storage, publisher)?
+        .map_or_else(Uint128::zero, |bond| bond.amount);
+    if bonded < min_bond {
+        return Err(ContractError::InsufficientBond { bonded, min_bond });
+    }
+    Ok(())
+}
+
+// Gives a strike to a publisher whose price is too far from the median, and
+// slashes its stake to the treasury once it has collected enough strikes
+fn strike_outlier(
+    storage: &mut dyn Storage,
+    env: &Env,
+    publisher: &Addr,
+    price: Decimal,
+    median: Decimal,
+) -> StdResult<Option<BankMsg>> {
+    let mut state = STATE.load(storage)?;
+    let bonding = match &state.bonding {
+        Some(bonding) => bonding.clone(),
+        None => return Ok(None),
+    };
+    if !deviates(price, median, bonding.max_deviation) {
+        return Ok(None);
+    }
+
+    let mut bond = BONDS.may_load(storage, publisher)?.unwrap_or_default();
+    bond.strikes += 1;
+    if bond.strikes < bonding.strikes_to_slash {
+        BONDS.save(storage, publisher, &bond)?;
+        return Ok(None);
+    }
+
+    // Bonded stake is slashed first, then stake that is still unbonding
+    bond.strikes = 0;
+    let staked = bond
+        .unbonding
+        .iter()
+        .fold(bond.amount, |sum, unbonding| sum + unbonding.amount);
+    let amount = bonding.slash_ratio * staked;
+    let mut remaining = amount;
+    for stake in std::iter::once(&mut bond.amount)
+        .chain(bond.unbonding.iter_mut().rev().map(|u| &mut u.amount))
+    {
+        let taken = remaining.min(*stake);
+        *stake -= taken;
+        remaining -= taken;
+    }
+    bond.unbonding
+        .retain(|unbonding| !unbonding.amount.is_zero());
+    BONDS.save(storage, publisher, &bond)?;
+    if amount.is_zero() {
+        return Ok(None);
+    }
+
+    SLASHES.save(
+        storage,
+        U64Key::new(state.slash_count),
+        &Slash {
+            id: state.slash_count,
+            publisher: publisher.clone(),
+            amount,
+            block_height: env.block.height,
+            block_time: env.block.time,
+        },
+    )?;
+    state.slash_count += 1;
+    STATE.save(storage, &state)?;
+
+    Ok(Some(BankMsg::Send {
+        to_address: bonding.treasury.to_string(),
+        amount: coins(amount.u128(), BOND_DENOM),
+    }))
+}
+
+fn ensure_not_paused(storage: &dyn Storage) -> Result<(), ContractError> {
+    if STATE.load(storage)?.paused {
+        return Err(ContractError::Paused {});
+    }
+    Ok(())
+}
+
+// Consumers query prices right before trading, a paused oracle must make them fail
+fn check_not_paused(storage: &dyn Storage) -> StdResult<()> {
+    if STATE.load(storage)?.paused {
+        return Err(StdError::generic_err(ContractError::Paused {}.to_string()));
+    }
+    Ok(())
+}
+
+fn validate_price(price: Decimal, decimals: u8) -> Result<(), ContractError> {
+    if price.is_zero() {
+        return Err(ContractError::PriceInstantiationError {});
+    }
+    if decimals > MAX_DECIMALS {
+        return Err(ContractError::InvalidDecimals { decimals });
+    }
+    if truncate(price, decimals) != price {
+        return Err(ContractError::PricePrecision { decimals });
+    }
+    Ok(())
+}
+
+// Resolves an optional feed to the default one and loads it with its storage key
+fn load_feed(storage: &dyn Storage, feed: Option<FeedId>) -> StdResult<(Vec<u8>, Feed)> {
+    let feed = match feed {
+        Some(feed) => feed,
+        None => STATE.load(storage)?.default_feed,
+    };
+
+    let key = feed_key(&feed);
+    let feed = FEEDS
+        .may_load(storage, &key)?
+        .ok_or_else(|| StdError::not_found(format!("feed {}", feed_label(&feed))))?;
+    Ok((key, feed))
+}
+
+fn validate_feed_id(deps: Deps, feed: FeedId) -> StdResult<FeedId> {
+    let asset = match feed.asset {
+        AssetId::Cw20 { contract_addr } => AssetId::Cw20 {
+            contract_addr: deps.api.addr_validate(&contract_addr)?.to_string(),
+        },
+        AssetId::Native { denom } if !denom.is_empty() => AssetId::Native { denom },
+        AssetId::Native { .. } => return Err(StdError::generic_err("Empty asset denom")),
+    };
+    if feed.quote_denom.is_empty() {
+        return Err(StdError::generic_err("Empty quote denom"));
+    }
+
+    Ok(FeedId {
+        asset,
+        quote_denom: feed.quote_denom,
+    })
+}
+
+// Human readable form of a feed for attributes and errors, e.g. "cw20:terra1.../uluna"
+fn feed_label(feed: &FeedId) -> String {
+    match &feed.asset {
+        AssetId::Cw20 { contract_addr } => format!("cw20:{}/{}", contract_addr, feed.quote_denom),
+        AssetId::Native { denom } => format!("native:{}/{}", denom, feed.quote_denom),
+    }
+}
+
+fn to_string(key: Vec<u8>) -> StdResult<String> {
+    String::from_utf8(key).map_err(StdError::invalid_utf8)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use cosmwasm_std::testing::{mock_dependencies, mock_env, mock_info, MOCK_CONTRACT_ADDR};
+    use cosmwasm_std::testing::{MockApi, MockQuerier, MockStorage};
+    use cosmwasm_std::{
+        coins, Attribute, CosmosMsg, OwnedDeps, ReplyOn, SystemError, SystemResult,
+    };
+    use cosmwasm_std::{
+        from_slice, Querier, QuerierResult, QuerierWrapper, QueryRequest, WasmQuery,
+    };
+    use k256::ecdsa::signature::Signer as _;
+    use k256::ecdsa::{Signature, SigningKey};
+    use shared::access::{AccessExecuteMsg, AccessQueryMsg, RoleMembersResponse};
+    use shared::oracle::OracleContract;
+    use std::str::FromStr;
+    use terra_cosmwasm::{ExchangeRateItem, ExchangeRatesResponse, TerraQuery, TerraQueryWrapper};
+
+    fn aurm_feed() -> FeedId {
+        FeedId {
+            asset: AssetId::Cw20 {
+                contract_addr: "aurm_token".to_string(),
+            },
+            quote_denom: "uluna".to_string(),
+        }
+    }
+
+    #[test]
+    fn proper_initialization() {
+        let mut deps = mock_dependencies(&[]);
+
+        let msg = InstantiateMsg {
+            price: Decimal::from_ratio(17u128, 1u128),
+            default_feed: aurm_feed(),
+            decimals: 6,
+            publishers: vec![],
+            max_price_age: None,
+            history_size: None,
+            admin: None,
+        };
+        let info = mock_info("creator", &coins(1000, "earth"));
+
+        // we can just call .unwrap() to assert this was a success
+        let res = instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+        assert_eq!(0, res.messages.len());
+
+        // it worked, let's query the state
+        let res: PriceResponse = from_binary(
+            &query(
+                deps.as_ref(),
+                mock_env(),
+                QueryMsg::QueryPrice {
+                    feed: None,
+                    max_age_seconds: None,
+                },
+            )
+            .unwrap(),
+        )
+        .unwrap();
+        assert_eq!(17, res.price);
+    }
+
+    #[test]
+    fn error_on_unauthorized_update() {
+        let mut deps = mock_dependencies(&[]);
+
+        let msg = InstantiateMsg {
+            price: Decimal::from_ratio(10u128, 1u128),
+            default_feed: aurm_feed(),
+            decimals: 6,
+            publishers: vec![],
+            max_price_age: None,
+            history_size: None,
+            admin: None,
+        };
+        let info = mock_info("creator", &[]);
+
+        let _res = instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+        // Try updating price from a non-owner address
+        let msg = ExecuteMsg::UpdatePrice {
+            feed: None,
+            price: Decimal::from_ratio(58u128, 1u128),
+        };
+        let info = mock_info("not_creator", &[]);
+
+        // Check that Unauthorized Error is thrown
+        let err = execute(deps.as_mut(), mock_env(), info, msg).unwrap_err();
+        match err {
+            ContractError::Access(AccessError::Unauthorized {}) => {}
+            _ => panic!("Expected AccessError::Unauthorized. Got something different."),
+        }
+
+        // Check that price is unchanged from instantiation
+        let res: PriceResponse = from_binary(
+            &query(
+                deps.as_ref(),
+                mock_env(),
+                QueryMsg::QueryPrice {
+                    feed: None,
+                    max_age_seconds: None,
+                },
+            )
+            .unwrap(),
+        )
+        .unwrap();
+        assert_eq!(10, res.price);
+    }
+
+    #[test]
+    fn owner_can_update() {
+        let mut deps = mock_dependencies(&[]);
+
+        let msg = InstantiateMsg {
+            price: Decimal::from_ratio(10u128, 1u128),
+            default_feed: aurm_feed(),
+            decimals: 6,
+            publishers: vec![],
+            max_price_age: None,
+            history_size: None,
+            admin: None,
+        };
+        let info = mock_info("creator", &[]);
+
+        let _res = instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+        // Try updating price from owner
+        let info = mock_info("creator", &[]);
+        let msg = ExecuteMsg::UpdatePrice {
+            feed: None,
+            price: Decimal::from_ratio(58u128, 1u128),
+        };
+        let res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+        // Check response attributes
+        let Attribute { key, value } = res.attributes.first().unwrap();
+        assert_eq!("method", key);
+        assert_eq!("try_update_price", value);
+
+        let Attribute { key, value } = res.attributes.get(1).unwrap();
+        assert_eq!("publisher", key);
+        assert_eq!("creator", value);
+
+        let Attribute { key, value } = res.attributes.get(2).unwrap();
+        assert_eq!("old_price", key);
+        assert_eq!("10", value);
+
+        let Attribute { key, value } = res.attributes.get(3).unwrap();
+        assert_eq!("new_price", key);
+        assert_eq!("58", value);
+
+        // Check that price is updated to new value
+        let res: PriceResponse = from_binary(
+            &query(
+                deps.as_ref(),
+                mock_env(),
+                QueryMsg::QueryPrice {
+                    feed: None,
+                    max_age_seconds: None,
+                },
+            )
+            .unwrap(),
+        )
+        .unwrap();
+        assert_eq!(58, res.price);
+    }
+
+    #[test]
+    fn price_is_median_of_publishers() {
+        let mut deps = mock_dependencies(&[]);
+
+        let msg = InstantiateMsg {
+            price: Decimal::from_ratio(10u128, 1u128),
+            default_feed: aurm_feed(),
+            decimals: 6,
+            publishers: vec!["alice".to_string(), "bob".to_string()],
+            max_price_age: None,
+            history_size: None,
+            admin: None,
+        };
+        let info = mock_info("creator", &[]);
+        let _res = instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+        // Two submissions: the median is the mean of both
+        let info = mock_info("alice", &[]);
+        let msg = ExecuteMsg::UpdatePrice {
+            feed: None,
+            price: Decimal::from_ratio(20u128, 1u128),
+        };
+        let _res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+        // Three submissions: the median ignores the outlier
+        let info = mock_info("bob", &[]);
+        let msg = ExecuteMsg::UpdatePrice {
+            feed: None,
+            price: Decimal::from_ratio(1000u128, 1u128),
+        };
+        let _res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+        let res: PriceResponse = from_binary(
+            &query(
+                deps.as_ref(),
+                mock_env(),
+                QueryMsg::QueryPrice {
+                    feed: None,
+                    max_age_seconds: None,
+                },
+            )
+            .unwrap(),
+        )
+        .unwrap();
+        assert_eq!(20, res.price);
+
+        // The outlier widens the band less than the mean deviation would
+        let res: PriceResponseV2 = from_binary(
+            &query(
+                deps.as_ref(),
+                mock_env(),
+                QueryMsg::QueryPriceV2 {
+                    feed: None,
+                    max_age_seconds: None,
+                    kind: None,
+                },
+            )
+            .unwrap(),
+        )
+        .unwrap();
+        assert_eq!(Decimal::from_ratio(10u128, 1u128), res.confidence);
+        assert_eq!(3, res.publishers);
+
+        // Removing a publisher drops its submission too
+        let info = mock_info("creator", &[]);
+        let msg = ExecuteMsg::RemovePublisher {
+            address: "alice".to_string(),
+        };
+        let _res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+        let res: PriceResponse = from_binary(
+            &query(
+                deps.as_ref(),
+                mock_env(),
+                QueryMsg::QueryPrice {
+                    feed: None,
+                    max_age_seconds: None,
+                },
+            )
+            .unwrap(),
+        )
+        .unwrap();
+        assert_eq!(505, res.price);
+
+        let info = mock_info("alice", &[]);
+        let msg = ExecuteMsg::UpdatePrice {
+            feed: None,
+            price: Decimal::from_ratio(30u128, 1u128),
+        };
+        let err = execute(deps.as_mut(), mock_env(), info, msg).unwrap_err();
+        assert!(matches!(
+            err,
+            ContractError::Access(AccessError::Unauthorized {})
+        ));
+    }
+
+    #[test]
+    fn stale_submissions_are_ignored() {
+        let mut deps = mock_dependencies(&[]);
+
+        let msg = InstantiateMsg {
+            price: Decimal::from_ratio(10u128, 1u128),
+            default_feed: aurm_feed(),
+            decimals: 6,
+            publishers: vec!["alice".to_string()],
+            max_price_age: Some(60),
+            history_size: None,
+            admin: None,
+        };
+        let info = mock_info("creator", &[]);
+        let _res = instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+        let mut env = mock_env();
+        env.block.time = env.block.time.plus_seconds(61);
+        env.block.height += 10;
+
+        let info = mock_info("alice", &[]);
+        let msg = ExecuteMsg::UpdatePrice {
+            feed: None,
+            price: Decimal::from_ratio(40u128, 1u128),
+        };
+        let _res = execute(deps.as_mut(), env.clone(), info, msg).unwrap();
+
+        // The owner's instantiation price is now too old to count
+        let res: PriceResponse = from_binary(
+            &query(
+                deps.as_ref(),
+                env.clone(),
+                QueryMsg::QueryPrice {
+                    feed: None,
+                    max_age_seconds: None,
+                },
+            )
+            .unwrap(),
+        )
+        .unwrap();
+        assert_eq!(40, res.price);
+
+        env.block.time = env.block.time.plus_seconds(61);
+        let err = query(
+            deps.as_ref(),
+            env,
+            QueryMsg::QueryPrice {
+                feed: None,
+                max_age_seconds: None,
+            },
+        )
+        .unwrap_err();
+        assert_eq!(err, StdError::generic_err("No fresh price submissions"));
+    }
+
+    #[test]
+    fn list_publishers() {
+        let mut deps = mock_dependencies(&[]);
+
+        let msg = InstantiateMsg {
+            price: Decimal::from_ratio(10u128, 1u128),
+            default_feed: aurm_feed(),
+            decimals: 6,
+            publishers: vec![],
+            max_price_age: None,
+            history_size: None,
+            admin: None,
+        };
+        let info = mock_info("creator", &[]);
+        let _res = instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+        // Only the admin may approve publishers
+        let info = mock_info("alice", &[]);
+        let msg = ExecuteMsg::AddPublisher {
+            address: "alice".to_string(),
+        };
+        let err = execute(deps.as_mut(), mock_env(), info, msg.clone()).unwrap_err();
+        assert!(matches!(
+            err,
+            ContractError::Access(AccessError::Unauthorized {})
+        ));
+
+        let info = mock_info("creator", &[]);
+        let _res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+        let msg = QueryMsg::QueryPublishers {
+            feed: None,
+            start_after: None,
+            limit: None,
+        };
+        let res: PublishersResponse =
+            from_binary(&query(deps.as_ref(), mock_env(), msg).unwrap()).unwrap();
+        assert_eq!(2, res.publishers.len());
+        assert_eq!("alice", res.publishers[0].address);
+        assert_eq!(None, res.publishers[0].last_submission);
+        assert_eq!("creator", res.publishers[1].address);
+        assert_eq!(
+            Decimal::from_ratio(10u128, 1u128),
+            res.publishers[1].last_submission.as_ref().unwrap().price
+        );
+
+        let msg = QueryMsg::QueryPublishers {
+            feed: None,
+            start_after: Some("alice".to_string()),
+            limit: Some(1),
+        };
+        let res: PublishersResponse =
+            from_binary(&query(deps.as_ref(), mock_env(), msg).unwrap()).unwrap();
+        assert_eq!(1, res.publishers.len());
+        assert_eq!("creator", res.publishers[0].address);
+
+        let msg = QueryMsg::QueryPublisherPrice {
+            feed: None,
+            address: "creator".to_string(),
+        };
+        let res: PublisherResponse =
+            from_binary(&query(deps.as_ref(), mock_env(), msg).unwrap()).unwrap();
+        assert_eq!(
+            mock_env().block.height,
+            res.last_submission.unwrap().block_height
+        );
+    }
+
+    #[test]
+    fn price_carries_update_time() {
+        let mut deps = mock_dependencies(&[]);
+
+        let msg = InstantiateMsg {
+            price: Decimal::from_ratio(10u128, 1u128),
+            default_feed: aurm_feed(),
+            decimals: 6,
+            publishers: vec![],
+            max_price_age: None,
+            history_size: None,
+            admin: None,
+        };
+        let info = mock_info("creator", &[]);
+        let _res = instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+        let mut env = mock_env();
+        env.block.time = env.block.time.plus_seconds(30);
+        env.block.height += 5;
+
+        let info = mock_info("creator", &[]);
+        let msg = ExecuteMsg::UpdatePrice {
+            feed: None,
+            price: Decimal::from_ratio(12u128, 1u128),
+        };
+        let _res = execute(deps.as_mut(), env.clone(), info, msg).unwrap();
+
+        let res: PriceResponse = from_binary(
+            &query(
+                deps.as_ref(),
+                env.clone(),
+                QueryMsg::QueryPrice {
+                    feed: None,
+                    max_age_seconds: Some(0),
+                },
+            )
+            .unwrap(),
+        )
+        .unwrap();
+        assert_eq!(12, res.price);
+        assert_eq!(env.block.height, res.last_updated_height);
+        assert_eq!(env.block.time, res.last_updated_time);
+
+        // Twenty seconds later the price is too old for a 10 second max age
+        env.block.time = env.block.time.plus_seconds(20);
+        let msg = QueryMsg::QueryPrice {
+            feed: None,
+            max_age_seconds: Some(10),
+        };
+        let err = query(deps.as_ref(), env.clone(), msg).unwrap_err();
+        assert_eq!(
+            err,
+            StdError::generic_err(
+                "Price is stale: last updated 20 seconds ago, max age is 10 seconds"
+            )
+        );
+
+        let msg = QueryMsg::QueryPrice {
+            feed: None,
+            max_age_seconds: Some(20),
+        };
+        assert!(query(deps.as_ref(), env, msg).is_ok());
+    }
+
+    #[test]
+    fn register_and_remove_feeds() {
+        let mut deps = mock_dependencies(&[]);
+
+        let msg = InstantiateMsg {
+            price: Decimal::from_ratio(10u128, 1u128),
+            default_feed: aurm_feed(),
+            decimals: 6,
+            publishers: vec!["alice".to_string()],
+            max_price_age: None,
+            history_size: None,
+            admin: None,
+        };
+        let info = mock_info("creator", &[]);
+        let _res = instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+        let luna_feed = FeedId {
+            asset: AssetId::Native {
+                denom: "uluna".to_string(),
+            },
+            quote_denom: "uusd".to_string(),
+        };
+
+        // Publishers cannot register feeds
+        let info = mock_info("alice", &[]);
+        let msg = ExecuteMsg::RegisterFeed {
+            feed: luna_feed.clone(),
+            decimals: 6,
+        };
+        let err = execute(deps.as_mut(), mock_env(), info, msg.clone()).unwrap_err();
+        assert!(matches!(
+            err,
+            ContractError::Access(AccessError::Unauthorized {})
+        ));
+
+        let info = mock_info("creator", &[]);
+        let _res = execute(deps.as_mut(), mock_env(), info.clone(), msg.clone()).unwrap();
+        let err = execute(deps.as_mut(), mock_env(), info, msg).unwrap_err();
+        assert!(matches!(err, ContractError::FeedAlreadyExists { .. }));
+
+        // A new feed has no price until someone publishes one
+        let msg = QueryMsg::QueryFeed {
+            feed: luna_feed.clone(),
+        };
+        let res: FeedResponse =
+            from_binary(&query(deps.as_ref(), mock_env(), msg).unwrap()).unwrap();
+        assert_eq!(luna_feed, res.id);
+        assert_eq!(None, res.price);
+
+        let info = mock_info("alice", &[]);
+        let msg = ExecuteMsg::UpdatePrice {
+            feed: Some(luna_feed.clone()),
+            price: Decimal::from_ratio(80u128, 1u128),
+        };
+        let _res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+        // Feeds are priced independently
+        let msg = QueryMsg::QueryPrice {
+            feed: Some(luna_feed.clone()),
+            max_age_seconds: None,
+        };
+        let res: PriceResponse =
+            from_binary(&query(deps.as_ref(), mock_env(), msg).unwrap()).unwrap();
+        assert_eq!(80, res.price);
+
+        let msg = QueryMsg::QueryPrice {
+            feed: None,
+            max_age_seconds: None,
+        };
+        let res: PriceResponse =
+            from_binary(&query(deps.as_ref(), mock_env(), msg).unwrap()).unwrap();
+        assert_eq!(10, res.price);
+
+        let msg = QueryMsg::QueryFeeds {
+            start_after: None,
+            limit: None,
+        };
+        let res: FeedsResponse =
+            from_binary(&query(deps.as_ref(), mock_env(), msg).unwrap()).unwrap();
+        assert_eq!(2, res.feeds.len());
+
+        let msg = QueryMsg::QueryFeeds {
+            start_after: Some(res.feeds[0].id.clone()),
+            limit: None,
+        };
+        let next: FeedsResponse =
+            from_binary(&query(deps.as_ref(), mock_env(), msg).unwrap()).unwrap();
+        assert_eq!(vec![res.feeds[1].clone()], next.feeds);
+
+        // The default feed stays, any other feed can be removed
+        let info = mock_info("creator", &[]);
+        let msg = ExecuteMsg::RemoveFeed { feed: aurm_feed() };
+        let err = execute(deps.as_mut(), mock_env(), info.clone(), msg).unwrap_err();
+        assert!(matches!(err, ContractError::DefaultFeedRemoval {}));
+
+        let msg = ExecuteMsg::RemoveFeed {
+            feed: luna_feed.clone(),
+        };
+        let _res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+        let msg = QueryMsg::QueryFeed { feed: luna_feed };
+        assert!(query(deps.as_ref(), mock_env(), msg).is_err());
+    }
+
+    #[test]
+    fn decimal_prices() {
+        let mut deps = mock_dependencies(&[]);
+
+        let msg = InstantiateMsg {
+            price: Decimal::from_str("0.25").unwrap(),
+            default_feed: aurm_feed(),
+            decimals: 2,
+            publishers: vec!["alice".to_string()],
+            max_price_age: None,
+            history_size: None,
+            admin: None,
+        };
+        let info = mock_info("creator", &[]);
+        let _res = instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+        // Prices finer than the feed precision are rejected
+        let info = mock_info("alice", &[]);
+        let msg = ExecuteMsg::UpdatePrice {
+            feed: None,
+            price: Decimal::from_str("1.255").unwrap(),
+        };
+        let err = execute(deps.as_mut(), mock_env(), info.clone(), msg).unwrap_err();
+        assert!(matches!(err, ContractError::PricePrecision { decimals: 2 }));
+
+        let msg = ExecuteMsg::UpdatePrice {
+            feed: None,
+            price: Decimal::from_str("1.5").unwrap(),
+        };
+        let _res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+        // The median of 0.25 and 1.5 is 0.875, rounded down to 0.87
+        let msg = QueryMsg::QueryPriceV2 {
+            feed: None,
+            max_age_seconds: None,
+            kind: None,
+        };
+        let res: PriceResponseV2 =
+            from_binary(&query(deps.as_ref(), mock_env(), msg).unwrap()).unwrap();
+        assert_eq!(Decimal::from_str("0.87").unwrap(), res.price);
+        assert_eq!(2, res.decimals);
+        assert_eq!("uluna", res.quote_denom);
+
+        // The legacy query keeps answering in whole units
+        let msg = QueryMsg::QueryPrice {
+            feed: None,
+            max_age_seconds: None,
+        };
+        let res: PriceResponse =
+            from_binary(&query(deps.as_ref(), mock_env(), msg).unwrap()).unwrap();
+        assert_eq!(0, res.price);
+
+        let info = mock_info("creator", &[]);
+        let msg = ExecuteMsg::RegisterFeed {
+            feed: FeedId {
+                asset: AssetId::Native {
+                    denom: "uluna".to_string(),
+                },
+                quote_denom: "uusd".to_string(),
+            },
+            decimals: 19,
+        };
+        let err = execute(deps.as_mut(), mock_env(), info, msg).unwrap_err();
+        assert!(matches!(
+            err,
+            ContractError::InvalidDecimals { decimals: 19 }
+        ));
+    }
+
+    #[test]
+    fn price_history_and_twap() {
+        let mut deps = mock_dependencies(&[]);
+
+        let msg = InstantiateMsg {
+            price: Decimal::from_ratio(10u128, 1u128),
+            default_feed: aurm_feed(),
+            decimals: 6,
+            publishers: vec![],
+            max_price_age: None,
+            history_size: Some(3),
+            admin: None,
+        };
+        let info = mock_info("creator", &[]);
+        let _res = instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+        // 10 for 100s, 20 for 100s, 40 for 100s, then 30
+        let mut env = mock_env();
+        for price in [20u128, 40, 30] {
+            env.block.time = env.block.time.plus_seconds(100);
+            env.block.height += 1;
+            let info = mock_info("creator", &[]);
+            let msg = ExecuteMsg::UpdatePrice {
+                feed: None,
+                price: Decimal::from_ratio(price, 1u128),
+            };
+            let _res = execute(deps.as_mut(), env.clone(), info, msg).unwrap();
+        }
+
+        // Only the last three observations are kept, newest first
+        let msg = QueryMsg::QueryPriceHistory {
+            feed: None,
+            start_after: None,
+            limit: None,
+        };
+        let res: PriceHistoryResponse =
+            from_binary(&query(deps.as_ref(), env.clone(), msg).unwrap()).unwrap();
+        let sequences: Vec<u64> = res.observations.iter().map(|o| o.sequence).collect();
+        assert_eq!(vec![3, 2, 1], sequences);
+        assert_eq!(
+            Decimal::from_ratio(30u128, 1u128),
+            res.observations[0].price
+        );
+        assert_eq!(env.block.time, res.observations[0].block_time);
+
+        let msg = QueryMsg::QueryPriceHistory {
+            feed: None,
+            start_after: Some(3),
+            limit: Some(1),
+        };
+        let res: PriceHistoryResponse =
+            from_binary(&query(deps.as_ref(), env.clone(), msg).unwrap()).unwrap();
+        assert_eq!(1, res.observations.len());
+        assert_eq!(2, res.observations[0].sequence);
+
+        // Over the last 150s: 40 for 100s and 30 for 50s
+        env.block.time = env.block.time.plus_seconds(50);
+        let msg = QueryMsg::QueryTwap {
+            feed: None,
+            window_seconds: 150,
+        };
+        let res: TwapResponse =
+            from_binary(&query(deps.as_ref(), env.clone(), msg).unwrap()).unwrap();
+        assert_eq!(Decimal::from_str("36.666666").unwrap(), res.price);
+        assert_eq!("uluna", res.quote_denom);
+
+        // The window reaches back before the oldest kept observation
+        let msg = QueryMsg::QueryTwap {
+            feed: None,
+            window_seconds: 1000,
+        };
+        let res: TwapResponse = from_binary(&query(deps.as_ref(), env, msg).unwrap()).unwrap();
+        assert_eq!(Decimal::from_str("30").unwrap(), res.price);
+    }
+
+    #[test]
+    fn circuit_breaker_rejects_out_of_bound_prices() {
+        let mut deps = mock_dependencies(&[]);
+
+        let msg = InstantiateMsg {
+            price: Decimal::from_ratio(100u128, 1u128),
+            default_feed: aurm_feed(),
+            decimals: 6,
+            publishers: vec![],
+            max_price_age: None,
+            history_size: None,
+            admin: None,
+        };
+        let info = mock_info("creator", &[]);
+        let _res = instantiate(deps.as_mut(), mock_env(), info.clone(), msg).unwrap();
+
+        // Zero is never a valid price
+        let msg = ExecuteMsg::UpdatePrice {
+            feed: None,
+            price: Decimal::zero(),
+        };
+        let err = execute(deps.as_mut(), mock_env(), info.clone(), msg).unwrap_err();
+        assert!(matches!(err, ContractError::PriceInstantiationError {}));
+
+        let msg = ExecuteMsg::SetPriceBounds {
+            feed: None,
+            bounds: PriceBounds {
+                min_price: Some(Decimal::from_ratio(50u128, 1u128)),
+                max_price: Some(Decimal::from_ratio(1000u128, 1u128)),
+                max_change: Some(Decimal::percent(10)),
+                hold_for_review: false,
+            },
+        };
+        let err = execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("alice", &[]),
+            msg.clone(),
+        )
+        .unwrap_err();
+        assert!(matches!(
+            err,
+            ContractError::Access(AccessError::Unauthorized {})
+        ));
+        let _res = execute(deps.as_mut(), mock_env(), info.clone(), msg).unwrap();
+
+        let update = |price: u128| ExecuteMsg::UpdatePrice {
+            feed: None,
+            price: Decimal::from_ratio(price, 1u128),
+        };
+        let err = execute(deps.as_mut(), mock_env(), info.clone(), update(40)).unwrap_err();
+        assert!(matches!(err, ContractError::PriceBelowMinimum { .. }));
+        let err = execute(deps.as_mut(), mock_env(), info.clone(), update(2000)).unwrap_err();
+        assert!(matches!(err, ContractError::PriceAboveMaximum { .. }));
+        let err = execute(deps.as_mut(), mock_env(), info.clone(), update(111)).unwrap_err();
+        assert!(matches!(err, ContractError::PriceDeviation { .. }));
+
+        // Exactly 10% away from the last price is still allowed
+        let _res = execute(deps.as_mut(), mock_env(), info, update(110)).unwrap();
+        let msg = QueryMsg::QueryPrice {
+            feed: None,
+            max_age_seconds: None,
+        };
+        let res: PriceResponse =
+            from_binary(&query(deps.as_ref(), mock_env(), msg).unwrap()).unwrap();
+        assert_eq!(110, res.price);
+    }
+
+    #[test]
+    fn circuit_breaker_holds_prices_for_review() {
+        let mut deps = mock_dependencies(&[]);
+
+        let msg = InstantiateMsg {
+            price: Decimal::from_ratio(100u128, 1u128),
+            default_feed: aurm_feed(),
+            decimals: 6,
+            publishers: vec!["alice".to_string()],
+            max_price_age: None,
+            history_size: None,
+            admin: None,
+        };
+        let owner = mock_info("creator", &[]);
+        let _res = instantiate(deps.as_mut(), mock_env(), owner.clone(), msg).unwrap();
+
+        let msg = ExecuteMsg::SetPriceBounds {
+            feed: None,
+            bounds: PriceBounds {
+                min_price: None,
+                max_price: None,
+                max_change: Some(Decimal::percent(50)),
+                hold_for_review: true,
+            },
+        };
+        let _res = execute(deps.as_mut(), mock_env(), owner.clone(), msg).unwrap();
+
+        let msg = ExecuteMsg::UpdatePrice {
+            feed: None,
+            price: Decimal::from_ratio(1000u128, 1u128),
+        };
+        let res = execute(deps.as_mut(), mock_env(), mock_info("alice", &[]), msg).unwrap();
+        assert!(res
+            .attributes
+            .iter()
+            .any(|attr| attr.key == "pending_price" && attr.value == "1000"));
+
+        // The held price does not count until the admin confirms it
+        let msg = QueryMsg::QueryPendingPrices { feed: None };
+        let res: PendingPricesResponse =
+            from_binary(&query(deps.as_ref(), mock_env(), msg).unwrap()).unwrap();
+        assert_eq!(1, res.pending.len());
+        assert_eq!("alice", res.pending[0].publisher);
+
+        let msg = QueryMsg::QueryPrice {
+            feed: None,
+            max_age_seconds: None,
+        };
+        let res: PriceResponse =
+            from_binary(&query(deps.as_ref(), mock_env(), msg.clone()).unwrap()).unwrap();
+        assert_eq!(100, res.price);
+
+        let confirm = ExecuteMsg::ConfirmPendingPrice {
+            feed: None,
+            publisher: "alice".to_string(),
+        };
+        let err = execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("alice", &[]),
+            confirm.clone(),
+        )
+        .unwrap_err();
+        assert!(matches!(
+            err,
+            ContractError::Access(AccessError::Unauthorized {})
+        ));
+        let _res = execute(deps.as_mut(), mock_env(), owner.clone(), confirm.clone()).unwrap();
+
+        let res: PriceResponse =
+            from_binary(&query(deps.as_ref(), mock_env(), msg).unwrap()).unwrap();
+        assert_eq!(550, res.price);
+
+        let err = execute(deps.as_mut(), mock_env(), owner, confirm).unwrap_err();
+        assert!(matches!(err, ContractError::NoPendingPrice { .. }));
+    }
+
+    #[test]
+    fn removing_a_feed_drops_held_prices() {
+        let mut deps = mock_dependencies(&[]);
+
+        let msg = InstantiateMsg {
+            price: Decimal::from_ratio(10u128, 1u128),
+            default_feed: aurm_feed(),
+            decimals: 6,
+            publishers: vec!["alice".to_string()],
+            max_price_age: None,
+            history_size: None,
+            admin: None,
+        };
+        let owner = mock_info("creator", &[]);
+        let _res = instantiate(deps.as_mut(), mock_env(), owner.clone(), msg).unwrap();
+
+        let luna_feed = FeedId {
+            asset: AssetId::Native {
+                denom: "uluna".to_string(),
+            },
+            quote_denom: "uusd".to_string(),
+        };
+        let register = ExecuteMsg::RegisterFeed {
+            feed: luna_feed.clone(),
+            decimals: 6,
+        };
+        let _res = execute(deps.as_mut(), mock_env(), owner.clone(), register.clone()).unwrap();
+        let msg = ExecuteMsg::SetPriceBounds {
+            feed: Some(luna_feed.clone()),
+            bounds: PriceBounds {
+                min_price: None,
+                max_price: Some(Decimal::from_ratio(100u128, 1u128)),
+                max_change: None,
+                hold_for_review: true,
+            },
+        };
+        let _res = execute(deps.as_mut(), mock_env(), owner.clone(), msg).unwrap();
+
+        // Alice's first price is held, she has no submission on the feed yet
+        let msg = ExecuteMsg::UpdatePrice {
+            feed: Some(luna_feed.clone()),
+            price: Decimal::from_ratio(500u128, 1u128),
+        };
+        let _res = execute(deps.as_mut(), mock_env(), mock_info("alice", &[]), msg).unwrap();
+
+        let msg = ExecuteMsg::RemoveFeed {
+            feed: luna_feed.clone(),
+        };
+        let _res = execute(deps.as_mut(), mock_env(), owner.clone(), msg).unwrap();
+        let _res = execute(deps.as_mut(), mock_env(), owner.clone(), register).unwrap();
+
+        let msg = QueryMsg::QueryPendingPrices {
+            feed: Some(luna_feed.clone()),
+        };
+        let res: PendingPricesResponse =
+            from_binary(&query(deps.as_ref(), mock_env(), msg).unwrap()).unwrap();
+        assert!(res.pending.is_empty());
+        let confirm = ExecuteMsg::ConfirmPendingPrice {
+            feed: Some(luna_feed),
+            publisher: "alice".to_string(),
+        };
+        let err = execute(deps.as_mut(), mock_env(), owner, confirm).unwrap_err();
+        assert!(matches!(err, ContractError::NoPendingPrice { .. }));
+    }
+
+    #[test]
+    fn relayed_attestations() {
+        let mut deps = mock_dependencies(&[]);
+
+        let msg = InstantiateMsg {
+            price: Decimal::from_ratio(10u128, 1u128),
+            default_feed: aurm_feed(),
+            decimals: 6,
+            publishers: vec![],
+            max_price_age: None,
+            history_size: None,
+            admin: None,
+        };
+        let owner = mock_info("creator", &[]);
+        let _res = instantiate(deps.as_mut(), mock_env(), owner.clone(), msg).unwrap();
+
+        let key = SigningKey::from_bytes(&[7u8; 32]).unwrap();
+        let public_key = Binary::from(key.verifying_key().to_bytes().as_slice());
+        let msg = ExecuteMsg::AddSigner {
+            address: "feeder".to_string(),
+            public_key: public_key.clone(),
+        };
+        let err = execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("feeder", &[]),
+            msg.clone(),
+        )
+        .unwrap_err();
+        assert!(matches!(
+            err,
+            ContractError::Access(AccessError::Unauthorized {})
+        ));
+        let _res = execute(deps.as_mut(), mock_env(), owner, msg).unwrap();
+
+        let now = mock_env().block.time.seconds();
+        let attestation = |price: u128, timestamp: u64, nonce: u64| Attestation {
+            oracle: MOCK_CONTRACT_ADDR.to_string(),
+            chain_id: mock_env().block.chain_id,
+            feed: aurm_feed(),
+            price: Decimal::from_ratio(price, 1u128),
+            timestamp,
+            nonce,
+        };
+        let sign = |attestation: &Attestation| {
+            let payload = to_binary(attestation).unwrap();
+            let signature: Signature = key.sign(payload.as_slice());
+            ExecuteMsg::SubmitAttestation {
+                signer: "feeder".to_string(),
+                payload,
+                signature: Binary::from(signature.as_ref()),
+            }
+        };
+        let attest =
+            |price: u128, timestamp: u64, nonce: u64| sign(&attestation(price, timestamp, nonce));
+
+        // Prices signed for another oracle or chain are rejected
+        let other_oracle = Attestation {
+            oracle: "other_oracle".to_string(),
+            ..attestation(30, now - 60, 0)
+        };
+        let other_chain = Attestation {
+            chain_id: "other-chain".to_string(),
+            ..attestation(30, now - 60, 0)
+        };
+        for attestation in [other_oracle, other_chain] {
+            let err = execute(
+                deps.as_mut(),
+                mock_env(),
+                mock_info("relayer", &[]),
+                sign(&attestation),
+            )
+            .unwrap_err();
+            assert!(matches!(err, ContractError::AttestationWrongDomain { .. }));
+        }
+
+        // Anyone can relay a signed price, and it is timed by its signature
+        let msg = attest(30, now - 60, 0);
+        let _res = execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("relayer", &[]),
+            msg.clone(),
+        )
+        .unwrap();
+        let res: PublisherResponse = from_binary(
+            &query(
+                deps.as_ref(),
+                mock_env(),
+                QueryMsg::QueryPublisherPrice {
+                    feed: None,
+                    address: "feeder".to_string(),
+                },
+            )
+            .unwrap(),
+        )
+        .unwrap();
+        let submission = res.last_submission.unwrap();
+        assert_eq!(Decimal::from_ratio(30u128, 1u128), submission.price);
+        assert_eq!(now - 60, submission.block_time.seconds());
+
+        let msg_price = QueryMsg::QueryPriceV2 {
+            feed: None,
+            max_age_seconds: None,
+            kind: None,
+        };
+        let res: PriceResponseV2 =
+            from_binary(&query(deps.as_ref(), mock_env(), msg_price.clone()).unwrap()).unwrap();
+        assert_eq!(Decimal::from_ratio(20u128, 1u128), res.price);
+
+        // The same attestation cannot be submitted twice, nor an older one
+        let err = execute(deps.as_mut(), mock_env(), mock_info("other", &[]), msg).unwrap_err();
+        assert!(matches!(err, ContractError::AttestationReplay { .. }));
+        let err = execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("relayer", &[]),
+            attest(40, now - 120, 5),
+        )
+        .unwrap_err();
+        assert!(matches!(err, ContractError::AttestationReplay { .. }));
+
+        let err = execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("relayer", &[]),
+            attest(40, now + 60, 1),
+        )
+        .unwrap_err();
+        assert!(matches!(err, ContractError::AttestationFromFuture { .. }));
+
+        // A tampered payload no longer matches its signature
+        let ExecuteMsg::SubmitAttestation { signature, .. } = attest(40, now, 1) else {
+            panic!("expected an attestation");
+        };
+        let payload = to_binary(&attestation(4000, now, 1)).unwrap();
+        let msg = ExecuteMsg::SubmitAttestation {
+            signer: "feeder".to_string(),
+            payload,
+            signature,
+        };
+        let err = execute(deps.as_mut(), mock_env(), mock_info("relayer", &[]), msg).unwrap_err();
+        assert!(matches!(err, ContractError::InvalidSignature {}));
+
+        // Same timestamp with a higher nonce is accepted
+        let _res = execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("relayer", &[]),
+            attest(40, now - 60, 1),
+        )
+        .unwrap();
+        let res: PriceResponseV2 =
+            from_binary(&query(deps.as_ref(), mock_env(), msg_price).unwrap()).unwrap();
+        assert_eq!(Decimal::from_ratio(25u128, 1u128), res.price);
+
+        let res: SignersResponse = from_binary(
+            &query(
+                deps.as_ref(),
+                mock_env(),
+                QueryMsg::QuerySigners {
+                    start_after: None,
+                    limit: None,
+                },
+            )
+            .unwrap(),
+        )
+        .unwrap();
+        assert_eq!(1, res.signers.len());
+        assert_eq!("feeder", res.signers[0].address);
+        assert_eq!(public_key, res.signers[0].public_key);
+    }
+
+    // Commitment to `price` and `salt` by `publisher` on the AURM feed
+    fn commitment(publisher: &str, round: u64, price: &str, salt: &str) -> Binary {
+        let preimage = format!(
+            "{}:cw20:aurm_token/uluna:{}:{}:{}",
+            publisher, round, price, salt
+        );
+        Binary::from(Sha256::digest(preimage.as_bytes()).as_slice())
+    }
+
+    #[test]
+    fn commit_reveal_rounds() {
+        let mut deps = mock_dependencies(&[]);
+
+        let msg = InstantiateMsg {
+            price: Decimal::from_ratio(10u128, 1u128),
+            default_feed: aurm_feed(),
+            decimals: 6,
+            publishers: vec!["alice".to_string(), "bob".to_string()],
+            max_price_age: None,
+            history_size: None,
+            admin: None,
+        };
+        let owner = mock_info("creator", &[]);
+        let _res = instantiate(deps.as_mut(), mock_env(), owner.clone(), msg).unwrap();
+
+        let msg = ExecuteMsg::SetRoundConfig {
+            feed: None,
+            config: Some(RoundConfig {
+                commit_seconds: 60,
+                reveal_seconds: 60,
+            }),
+        };
+        let _res = execute(deps.as_mut(), mock_env(), owner, msg).unwrap();
+
+        // Prices can no longer be sent in the clear
+        let msg = ExecuteMsg::UpdatePrice {
+            feed: None,
+            price: Decimal::from_ratio(11u128, 1u128),
+        };
+        let err = execute(deps.as_mut(), mock_env(), mock_info("alice", &[]), msg).unwrap_err();
+        assert!(matches!(err, ContractError::RoundBasedFeed { .. }));
+
+        let msg = ExecuteMsg::StartRound { feed: None };
+        let _res = execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("alice", &[]),
+            msg.clone(),
+        )
+        .unwrap();
+        let err = execute(deps.as_mut(), mock_env(), mock_info("bob", &[]), msg).unwrap_err();
+        assert!(matches!(err, ContractError::WrongRoundPhase { .. }));
+
+        let commit = |publisher: &str, price: &str, salt: &str| ExecuteMsg::CommitPrice {
+            feed: None,
+            commitment: commitment(publisher, 0, price, salt),
+        };
+        let reveal = |price: u128, salt: &str| ExecuteMsg::RevealPrice {
+            feed: None,
+            price: Decimal::from_ratio(price, 1u128),
+            salt: salt.to_string(),
+        };
+        let _res = execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("alice", &[]),
+            commit("alice", "12", "a"),
+        )
+        .unwrap();
+        let _res = execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("bob", &[]),
+            commit("bob", "14", "b"),
+        )
+        .unwrap();
+        let err = execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("alice", &[]),
+            reveal(12, "a"),
+        )
+        .unwrap_err();
+        assert!(matches!(err, ContractError::WrongRoundPhase { .. }));
+
+        let mut env = mock_env();
+        env.block.time = env.block.time.plus_seconds(60);
+        let _res = execute(
+            deps.as_mut(),
+            env.clone(),
+            mock_info("alice", &[]),
+            reveal(12, "a"),
+        )
+        .unwrap();
+        let err = execute(
+            deps.as_mut(),
+            env.clone(),
+            mock_info("bob", &[]),
+            reveal(15, "b"),
+        )
+        .unwrap_err();
+        assert!(matches!(err, ContractError::CommitmentMismatch {}));
+        let _res = execute(
+            deps.as_mut(),
+            env.clone(),
+            mock_info("bob", &[]),
+            reveal(14, "b"),
+        )
+        .unwrap();
+
+        let msg = QueryMsg::QueryRound {
+            feed: None,
+            round: None,
+        };
+        let res: RoundResponse =
+            from_binary(&query(deps.as_ref(), env.clone(), msg).unwrap()).unwrap();
+        assert_eq!(RoundPhase::Reveal, res.phase);
+        assert_eq!(2, res.reveals);
+
+        let finalize = ExecuteMsg::FinalizeRound { feed: None };
+        let err = execute(
+            deps.as_mut(),
+            env.clone(),
+            mock_info("anyone", &[]),
+            finalize.clone(),
+        )
+        .unwrap_err();
+        assert!(matches!(err, ContractError::WrongRoundPhase { .. }));
+
+        // Once the reveal window is over anyone can finalize
+        env.block.time = env.block.time.plus_seconds(60);
+        let _res = execute(
+            deps.as_mut(),
+            env.clone(),
+            mock_info("anyone", &[]),
+            finalize,
+        )
+        .unwrap();
+
+        let msg = QueryMsg::QueryFinalizedRounds {
+            feed: None,
+            start_after: None,
+            limit: None,
+        };
+        let res: RoundsResponse =
+            from_binary(&query(deps.as_ref(), env.clone(), msg).unwrap()).unwrap();
+        assert_eq!(1, res.rounds.len());
+        assert_eq!(RoundPhase::Finalized, res.rounds[0].phase);
+        assert_eq!(
+            Some(Decimal::from_ratio(13u128, 1u128)),
+            res.rounds[0].round.price
+        );
+
+        // Revealed prices join the owner's initial price in the feed median
+        let msg = QueryMsg::QueryPriceV2 {
+            feed: None,
+            max_age_seconds: None,
+            kind: None,
+        };
+        let res: PriceResponseV2 =
+            from_binary(&query(deps.as_ref(), env.clone(), msg).unwrap()).unwrap();
+        assert_eq!(Decimal::from_ratio(12u128, 1u128), res.price);
+        assert_eq!(env.block.time, res.last_updated_time);
+
+        let msg = ExecuteMsg::StartRound { feed: None };
+        let res = execute(deps.as_mut(), env, mock_info("bob", &[]), msg).unwrap();
+        assert_eq!(
+            res.attributes[2],
+            Attribute {
+                key: "round".to_string(),
+                value: "1".to_string()
+            }
+        );
+    }
+
+    #[test]
+    fn copied_commitments_cannot_be_revealed() {
+        let mut deps = mock_dependencies(&[]);
+
+        let msg = InstantiateMsg {
+            price: Decimal::from_ratio(10u128, 1u128),
+            default_feed: aurm_feed(),
+            decimals: 6,
+            publishers: vec!["alice".to_string(), "bob".to_string()],
+            max_price_age: None,
+            history_size: None,
+            admin: None,
+        };
+        let owner = mock_info("creator", &[]);
+        let _res = instantiate(deps.as_mut(), mock_env(), owner.clone(), msg).unwrap();
+        let msg = ExecuteMsg::SetRoundConfig {
+            feed: None,
+            config: Some(RoundConfig {
+                commit_seconds: 60,
+                reveal_seconds: 60,
+            }),
+        };
+        let _res = execute(deps.as_mut(), mock_env(), owner, msg).unwrap();
+        let msg = ExecuteMsg::StartRound { feed: None };
+        let _res = execute(deps.as_mut(), mock_env(), mock_info("alice", &[]), msg).unwrap();
+
+        // Bob commits whatever alice committed, then replays her reveal
+        let copied = ExecuteMsg::CommitPrice {
+            feed: None,
+            commitment: commitment("alice", 0, "12", "a"),
+        };
+        for publisher in ["alice", "bob"] {
+            let _res = execute(
+                deps.as_mut(),
+                mock_env(),
+                mock_info(publisher, &[]),
+                copied.clone(),
+            )
+            .unwrap();
+        }
+
+        let mut env = mock_env();
+        env.block.time = env.block.time.plus_seconds(60);
+        let reveal = ExecuteMsg::RevealPrice {
+            feed: None,
+            price: Decimal::from_ratio(12u128, 1u128),
+            salt: "a".to_string(),
+        };
+        let _res = execute(
+            deps.as_mut(),
+            env.clone(),
+            mock_info("alice", &[]),
+            reveal.clone(),
+        )
+        .unwrap();
+        let err = execute(deps.as_mut(), env, mock_info("bob", &[]), reveal).unwrap_err();
+        assert!(matches!(err, ContractError::CommitmentMismatch {}));
+    }
+
+    #[test]
+    fn subscribers_receive_price_hooks() {
+        let mut deps = mock_dependencies(&[]);
+
+        let msg = InstantiateMsg {
+            price: Decimal::from_ratio(10u128, 1u128),
+            default_feed: aurm_feed(),
+            decimals: 6,
+            publishers: vec![],
+            max_price_age: None,
+            history_size: None,
+            admin: None,
+        };
+        let owner = mock_info("creator", &[]);
+        let _res = instantiate(deps.as_mut(), mock_env(), owner.clone(), msg).unwrap();
+
+        let swap = mock_info("swap_contract", &[]);
+        let msg = ExecuteMsg::Subscribe { feed: None };
+        let _res = execute(deps.as_mut(), mock_env(), swap.clone(), msg.clone()).unwrap();
+        let err = execute(deps.as_mut(), mock_env(), swap.clone(), msg).unwrap_err();
+        assert!(matches!(err, ContractError::AlreadySubscribed { .. }));
+
+        // Nothing is pushed until the admin approves the subscription
+        let update = ExecuteMsg::UpdatePrice {
+            feed: None,
+            price: Decimal::from_ratio(12u128, 1u128),
+        };
+        let res = execute(deps.as_mut(), mock_env(), owner.clone(), update.clone()).unwrap();
+        assert!(res.messages.is_empty());
+
+        let msg = ExecuteMsg::ApproveSubscriber {
+            feed: None,
+            subscriber: "swap_contract".to_string(),
+        };
+        let err = execute(deps.as_mut(), mock_env(), swap.clone(), msg.clone()).unwrap_err();
+        assert!(matches!(
+            err,
+            ContractError::Access(AccessError::Unauthorized {})
+        ));
+        let _res = execute(deps.as_mut(), mock_env(), owner.clone(), msg).unwrap();
+
+        let res = execute(deps.as_mut(), mock_env(), owner.clone(), update).unwrap();
+        assert_eq!(1, res.messages.len());
+        let hook = &res.messages[0];
+        assert_eq!(ReplyOn::Error, hook.reply_on);
+        assert_eq!(Some(HOOK_GAS_LIMIT), hook.gas_limit);
+        match &hook.msg {
+            CosmosMsg::Wasm(WasmMsg::Execute {
+                contract_addr, msg, ..
+            }) => {
+                assert_eq!("swap_contract", contract_addr);
+                let HookMsg::PriceUpdated { feed, price } = from_binary(msg).unwrap();
+                assert_eq!(aurm_feed(), feed);
+                assert_eq!(Decimal::from_ratio(12u128, 1u128), price.price);
+            }
+            msg => panic!("unexpected message {:?}", msg),
+        }
+
+        // A failing subscriber does not revert the update
+        let failure = Reply {
+            id: HOOK_REPLY_ID,
+            result: ContractResult::Err("subscriber failed".to_string()),
+        };
+        let res = reply(deps.as_mut(), mock_env(), failure).unwrap();
+        assert!(res
+            .attributes
+            .iter()
+            .any(|attr| attr.key == "hook_error" && attr.value == "subscriber failed"));
+
+        let msg = ExecuteMsg::Unsubscribe { feed: None };
+        let _res = execute(deps.as_mut(), mock_env(), swap, msg).unwrap();
+        let msg = QueryMsg::QuerySubscribers {
+            feed: None,
+            start_after: None,
+            limit: None,
+        };
+        let res: SubscribersResponse =
+            from_binary(&query(deps.as_ref(), mock_env(), msg).unwrap()).unwrap();
+        assert!(res.subscribers.is_empty());
+    }
+
+    #[test]
+    fn native_exchange_rate_reference() {
+        let custom = |query: &TerraQueryWrapper| match &query.query_data {
+            TerraQuery::ExchangeRates {
+                base_denom,
+                quote_denoms,
+            } if base_denom == "uluna" => {
+                let exchange_rates = quote_denoms
+                    .iter()
+                    .filter(|denom| *denom == "uusd")
+                    .map(|denom| ExchangeRateItem {
+                        quote_denom: denom.clone(),
+                        exchange_rate: Decimal::from_ratio(50u128, 1u128),
+                    })
+                    .collect();
+                let res = ExchangeRatesResponse {
+                    base_denom: base_denom.clone(),
+                    exchange_rates,
+                };
+                SystemResult::Ok(ContractResult::Ok(to_binary(&res).unwrap()))
+            }
+            _ => SystemResult::Err(SystemError::UnsupportedRequest {
+                kind: "terra".to_string(),
+            }),
+        };
+        let mut deps = OwnedDeps {
+            storage: MockStorage::default(),
+            api: MockApi::default(),
+            querier: MockQuerier::<TerraQueryWrapper>::new(&[]).with_custom_handler(custom),
+        };
+
+        let msg = InstantiateMsg {
+            price: Decimal::from_ratio(10u128, 1u128),
+            default_feed: aurm_feed(),
+            decimals: 6,
+            publishers: vec![],
+            max_price_age: None,
+            history_size: None,
+            admin: None,
+        };
+        let owner = mock_info("creator", &[]);
+        let _res = instantiate(deps.as_mut(), mock_env(), owner.clone(), msg).unwrap();
+
+        // AURM in uusd, chained from AURM in uluna at the LUNA/UST rate
+        let msg = QueryMsg::QueryDerivedPrice {
+            feed: None,
+            quote_denom: "uusd".to_string(),
+            max_age_seconds: None,
+        };
+        let res: PriceResponseV2 =
+            from_binary(&query(deps.as_ref(), mock_env(), msg).unwrap()).unwrap();
+        assert_eq!(Decimal::from_ratio(500u128, 1u128), res.price);
+        assert_eq!("uusd", res.quote_denom);
+
+        // Cw20 assets have no exchange rate of their own
+        let msg = ExecuteMsg::SetNativeReference {
+            feed: None,
+            reference: Some(NativeReference {
+                source: NativeSource::ExchangeRate {},
+                tolerance: Decimal::percent(5),
+            }),
+        };
+        let err = execute(deps.as_mut(), mock_env(), owner.clone(), msg).unwrap_err();
+        assert!(matches!(err, ContractError::InvalidNativeReference {}));
+
+        let aurm_usd = FeedId {
+            quote_denom: "uusd".to_string(),
+            ..aurm_feed()
+        };
+        let msg = ExecuteMsg::RegisterFeed {
+            feed: aurm_usd.clone(),
+            decimals: 6,
+        };
+        let _res = execute(deps.as_mut(), mock_env(), owner.clone(), msg).unwrap();
+        let msg = ExecuteMsg::SetNativeReference {
+            feed: Some(aurm_usd.clone()),
+            reference: Some(NativeReference {
+                source: NativeSource::Chained { via: aurm_feed() },
+                tolerance: Decimal::percent(5),
+            }),
+        };
+        let _res = execute(deps.as_mut(), mock_env(), owner.clone(), msg).unwrap();
+
+        let update = |price: u128| ExecuteMsg::UpdatePrice {
+            feed: Some(aurm_usd.clone()),
+            price: Decimal::from_ratio(price, 1u128),
+        };
+        let err = execute(deps.as_mut(), mock_env(), owner.clone(), update(600)).unwrap_err();
+        assert!(matches!(
+            err,
+            ContractError::NativeReferenceMismatch { reference, .. }
+                if reference == Decimal::from_ratio(500u128, 1u128)
+        ));
+        let _res = execute(deps.as_mut(), mock_env(), owner, update(510)).unwrap();
+    }
+
+    #[test]
+    fn two_step_admin_transfer() {
+        let mut deps = mock_dependencies(&[]);
+
+        let msg = InstantiateMsg {
+            price: Decimal::from_ratio(10u128, 1u128),
+            default_feed: aurm_feed(),
+            decimals: 6,
+            publishers: vec![],
+            max_price_age: None,
+            history_size: None,
+            admin: Some("admin".to_string()),
+        };
+        let _res = instantiate(deps.as_mut(), mock_env(), mock_info("creator", &[]), msg).unwrap();
+
+        // Publishing does not make the instantiator an admin
+        let msg = ExecuteMsg::AddPublisher {
+            address: "alice".to_string(),
+        };
+        let err = execute(deps.as_mut(), mock_env(), mock_info("creator", &[]), msg).unwrap_err();
+        assert!(matches!(
+            err,
+            ContractError::Access(AccessError::Unauthorized {})
+        ));
+
+        // Nor does the admin publish unless approved as a publisher
+        let msg = ExecuteMsg::UpdatePrice {
+            feed: None,
+            price: Decimal::from_ratio(11u128, 1u128),
+        };
+        let err = execute(deps.as_mut(), mock_env(), mock_info("admin", &[]), msg).unwrap_err();
+        assert!(matches!(
+            err,
+            ContractError::Access(AccessError::Unauthorized {})
+        ));
+
+        let msg = ExecuteMsg::Access(AccessExecuteMsg::AcceptOwnership {});
+        let err = execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("new_admin", &[]),
+            msg.clone(),
+        )
+        .unwrap_err();
+        assert!(matches!(
+            err,
+            ContractError::Access(AccessError::NoPendingOwner {})
+        ));
+
+        let propose = ExecuteMsg::Access(AccessExecuteMsg::TransferOwnership {
+            new_owner: "new_admin".to_string(),
+        });
+        let res = execute(deps.as_mut(), mock_env(), mock_info("admin", &[]), propose).unwrap();
+        assert_eq!(
+            res.attributes[2],
+            Attribute {
+                key: "pending_owner".to_string(),
+                value: "new_admin".to_string()
+            }
+        );
+
+        let err = execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("alice", &[]),
+            msg.clone(),
+        )
+        .unwrap_err();
+        assert!(matches!(
+            err,
+            ContractError::Access(AccessError::Unauthorized {})
+        ));
+
+        // The old admin stays in charge until the transfer is accepted
+        let res: RolesResponse =
+            from_binary(&query(deps.as_ref(), mock_env(), QueryMsg::QueryRoles {}).unwrap())
+                .unwrap();
+        assert_eq!(Some(Addr::unchecked("admin")), res.admin);
+        assert_eq!(Some(Addr::unchecked("new_admin")), res.pending_admin);
+
+        let _res = execute(deps.as_mut(), mock_env(), mock_info("new_admin", &[]), msg).unwrap();
+        let res: RolesResponse =
+            from_binary(&query(deps.as_ref(), mock_env(), QueryMsg::QueryRoles {}).unwrap())
+                .unwrap();
+        assert_eq!(Some(Addr::unchecked("new_admin")), res.admin);
+        assert_eq!(None, res.pending_admin);
+
+        let msg = ExecuteMsg::Access(AccessExecuteMsg::RenounceOwnership {});
+        let err = execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("admin", &[]),
+            msg.clone(),
+        )
+        .unwrap_err();
+        assert!(matches!(
+            err,
+            ContractError::Access(AccessError::Unauthorized {})
+        ));
+        let _res = execute(deps.as_mut(), mock_env(), mock_info("new_admin", &[]), msg).unwrap();
+
+        let msg = ExecuteMsg::AddPublisher {
+            address: "alice".to_string(),
+        };
+        let err = execute(deps.as_mut(), mock_env(), mock_info("new_admin", &[]), msg).unwrap_err();
+        assert!(matches!(
+            err,
+            ContractError::Access(AccessError::Unauthorized {})
+        ));
+        let res: RolesResponse =
+            from_binary(&query(deps.as_ref(), mock_env(), QueryMsg::QueryRoles {}).unwrap())
+                .unwrap();
+        assert_eq!(None, res.admin);
+    }
+
+    #[test]
+    fn guardian_can_pause_but_not_unpause() {
+        let mut deps = mock_dependencies(&[]);
+
+        let msg = InstantiateMsg {
+            price: Decimal::from_ratio(10u128, 1u128),
+            default_feed: aurm_feed(),
+            decimals: 6,
+            publishers: vec![],
+            max_price_age: None,
+            history_size: None,
+            admin: None,
+        };
+        let admin = mock_info("creator", &[]);
+        let _res = instantiate(deps.as_mut(), mock_env(), admin.clone(), msg).unwrap();
+
+        let guardian = mock_info("guardian", &[]);
+        let err = execute(
+            deps.as_mut(),
+            mock_env(),
+            guardian.clone(),
+            ExecuteMsg::Pause {},
+        )
+        .unwrap_err();
+        assert!(matches!(
+            err,
+            ContractError::Access(AccessError::Unauthorized {})
+        ));
+
+        let msg = ExecuteMsg::Access(AccessExecuteMsg::GrantRole {
+            role: GUARDIAN_ROLE.to_string(),
+            address: "guardian".to_string(),
+        });
+        let _res = execute(deps.as_mut(), mock_env(), admin.clone(), msg).unwrap();
+        let _res = execute(
+            deps.as_mut(),
+            mock_env(),
+            guardian.clone(),
+            ExecuteMsg::Pause {},
+        )
+        .unwrap();
+
+        // Prices are frozen: consumers can't read them and publishers can't move them
+        let query_msg = QueryMsg::QueryPriceV2 {
+            feed: None,
+            max_age_seconds: None,
+            kind: None,
+        };
+        let err = query(deps.as_ref(), mock_env(), query_msg.clone()).unwrap_err();
+        assert_eq!(err, StdError::generic_err("Oracle is paused"));
+        let msg = QueryMsg::QueryPrice {
+            feed: None,
+            max_age_seconds: None,
+        };
+        assert!(query(deps.as_ref(), mock_env(), msg).is_err());
+
+        let update = ExecuteMsg::UpdatePrice {
+            feed: None,
+            price: Decimal::from_ratio(11u128, 1u128),
+        };
+        let err = execute(deps.as_mut(), mock_env(), admin.clone(), update.clone()).unwrap_err();
+        assert!(matches!(err, ContractError::Paused {}));
+
+        let res: RolesResponse =
+            from_binary(&query(deps.as_ref(), mock_env(), QueryMsg::QueryRoles {}).unwrap())
+                .unwrap();
+        assert!(res.paused);
+        let msg = QueryMsg::Access(AccessQueryMsg::RoleMembers {
+            role: GUARDIAN_ROLE.to_string(),
+            start_after: None,
+            limit: None,
+        });
+        let res: RoleMembersResponse =
+            from_binary(&query(deps.as_ref(), mock_env(), msg).unwrap()).unwrap();
+        assert_eq!(vec![Addr::unchecked("guardian")], res.members);
+
+        let err = execute(deps.as_mut(), mock_env(), guardian, ExecuteMsg::Unpause {}).unwrap_err();
+        assert!(matches!(
+            err,
+            ContractError::Access(AccessError::Unauthorized {})
+        ));
+        let _res = execute(
+            deps.as_mut(),
+            mock_env(),
+            admin.clone(),
+            ExecuteMsg::Unpause {},
+        )
+        .unwrap();
+
+        let _res = execute(deps.as_mut(), mock_env(), admin, update).unwrap();
+        let res: PriceResponseV2 =
+            from_binary(&query(deps.as_ref(), mock_env(), query_msg).unwrap()).unwrap();
+        assert_eq!(Decimal::from_ratio(11u128, 1u128), res.price);
+    }
+
+    #[test]
+    fn paused_oracle_hides_feed_and_publisher_prices() {
+        let mut deps = mock_dependencies(&[]);
+
+        let msg = InstantiateMsg {
+            price: Decimal::from_ratio(10u128, 1u128),
+            default_feed: aurm_feed(),
+            decimals: 6,
+            publishers: vec![],
+            max_price_age: None,
+            history_size: None,
+            admin: None,
+        };
+        let admin = mock_info("creator", &[]);
+        let _res = instantiate(deps.as_mut(), mock_env(), admin.clone(), msg).unwrap();
+        let _res = execute(deps.as_mut(), mock_env(), admin, ExecuteMsg::Pause {}).unwrap();
+
+        let paused = StdError::generic_err("Oracle is paused");
+        let msgs = vec![
+            QueryMsg::QueryFeed { feed: aurm_feed() },
+            QueryMsg::QueryFeeds {
+                start_after: None,
+                limit: None,
+            },
+            QueryMsg::QueryPublisherPrice {
+                feed: None,
+                address: "creator".to_string(),
+            },
+            QueryMsg::QueryPublishers {
+                feed: None,
+                start_after: None,
+                limit: None,
+            },
+        ];
+        for msg in msgs {
+            let err = query(deps.as_ref(), mock_env(), msg.clone()).unwrap_err();
+            assert_eq!(err, paused, "{:?}", msg);
+        }
+    }
+
+    #[test]
+    fn ema_follows_spot_by_half_life() {
+        let mut deps = mock_dependencies(&[]);
+
+        let msg = InstantiateMsg {
+            price: Decimal::from_ratio(10u128, 1u128),
+            default_feed: aurm_feed(),
+            decimals: 6,
+            publishers: vec![],
+            max_price_age: None,
+            history_size: None,
+            admin: None,
+        };
+        let info = mock_info("creator", &[]);
+        let _res = instantiate(deps.as_mut(), mock_env(), info.clone(), msg).unwrap();
+
+        let msg = ExecuteMsg::SetEmaHalfLife {
+            feed: None,
+            half_life_seconds: 0,
+        };
+        let err = execute(deps.as_mut(), mock_env(), info.clone(), msg).unwrap_err();
+        assert!(matches!(err, ContractError::InvalidHalfLife {}));
+
+        let mut env = mock_env();
+        env.block.time = env.block.time.plus_seconds(DEFAULT_EMA_HALF_LIFE);
+        let msg = ExecuteMsg::UpdatePrice {
+            feed: None,
+            price: Decimal::from_ratio(20u128, 1u128),
+        };
+        let _res = execute(deps.as_mut(), env.clone(), info, msg).unwrap();
+
+        // Right after the jump the EMA still sits at the old price
+        let msg = QueryMsg::QueryEmaPrice { feed: None };
+        let res: EmaResponse =
+            from_binary(&query(deps.as_ref(), env.clone(), msg.clone()).unwrap()).unwrap();
+        assert_eq!(Decimal::from_ratio(10u128, 1u128), res.ema);
+        assert_eq!(Decimal::from_ratio(20u128, 1u128), res.spot);
+
+        // Half way there after half a half-life: 20 - 10 * 2^-0.5
+        env.block.time = env.block.time.plus_seconds(DEFAULT_EMA_HALF_LIFE / 2);
+        let res: EmaResponse =
+            from_binary(&query(deps.as_ref(), env.clone(), msg.clone()).unwrap()).unwrap();
+        assert_eq!(Decimal::from_str("12.928932").unwrap(), res.ema);
+
+        env.block.time = env.block.time.plus_seconds(DEFAULT_EMA_HALF_LIFE / 2);
+        let res: EmaResponse =
+            from_binary(&query(deps.as_ref(), env.clone(), msg).unwrap()).unwrap();
+        assert_eq!(Decimal::from_ratio(15u128, 1u128), res.ema);
+
+        // Consumers pick the conservative side of spot and EMA
+        let price = |kind: PriceKind| {
+            let msg = QueryMsg::QueryPriceV2 {
+                feed: None,
+                max_age_seconds: None,
+                kind: Some(kind),
+            };
+            let res: PriceResponseV2 =
+                from_binary(&query(deps.as_ref(), env.clone(), msg).unwrap()).unwrap();
+            res.price
+        };
+        assert_eq!(Decimal::from_ratio(20u128, 1u128), price(PriceKind::Spot));
+        assert_eq!(Decimal::from_ratio(15u128, 1u128), price(PriceKind::Ema));
+        assert_eq!(Decimal::from_ratio(15u128, 1u128), price(PriceKind::Min));
+        assert_eq!(Decimal::from_ratio(20u128, 1u128), price(PriceKind::Max));
+    }
+
+    #[test]
+    fn outliers_are_slashed_to_the_treasury() {
+        let mut deps = mock_dependencies(&[]);
+
+        let msg = InstantiateMsg {
+            price: Decimal::from_ratio(10u128, 1u128),
+            default_feed: aurm_feed(),
+            decimals: 6,
+            publishers: vec!["alice".to_string(), "bob".to_string()],
+            max_price_age: None,
+            history_size: None,
+            admin: None,
+        };
+        let admin = mock_info("creator", &[]);
+        let _res = instantiate(deps.as_mut(), mock_env(), admin.clone(), msg).unwrap();
+
+        let msg = ExecuteMsg::SetBondingConfig {
+            config: Some(BondingConfig {
+                min_bond: Uint128::new(1000),
+                max_deviation: Decimal::percent(10),
+                strikes_to_slash: 2,
+                slash_ratio: Decimal::percent(50),
+                treasury: "treasury".to_string(),
+                unbonding_seconds: 100,
+            }),
+        };
+        let _res = execute(deps.as_mut(), mock_env(), admin, msg).unwrap();
+
+        let update = |price: u128| ExecuteMsg::UpdatePrice {
+            feed: None,
+            price: Decimal::from_ratio(price, 1u128),
+        };
+        let err = execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("alice", &[]),
+            update(10),
+        )
+        .unwrap_err();
+        assert!(matches!(err, ContractError::InsufficientBond { .. }));
+
+        let info = mock_info("alice", &coins(1000, "uusd"));
+        let err = execute(deps.as_mut(), mock_env(), info, ExecuteMsg::Bond {}).unwrap_err();
+        assert!(matches!(err, ContractError::Payment(_)));
+        for (publisher, amount) in [("alice", 1000), ("bob", 2000)] {
+            let info = mock_info(publisher, &coins(amount, "uluna"));
+            let _res = execute(deps.as_mut(), mock_env(), info, ExecuteMsg::Bond {}).unwrap();
+        }
+
+        // Bob is twice the median of 10: a strike, but no slash yet
+        let _res = execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("alice", &[]),
+            update(10),
+        )
+        .unwrap();
+        let res = execute(deps.as_mut(), mock_env(), mock_info("bob", &[]), update(20)).unwrap();
+        assert!(res.messages.is_empty());
+
+        let msg = ExecuteMsg::Unbond {
+            amount: Uint128::new(400),
+        };
+        let _res = execute(deps.as_mut(), mock_env(), mock_info("bob", &[]), msg).unwrap();
+
+        // Second strike slashes half of everything bob has staked, unbonding included
+        let res = execute(deps.as_mut(), mock_env(), mock_info("bob", &[]), update(20)).unwrap();
+        assert_eq!(
+            vec![CosmosMsg::Bank(BankMsg::Send {
+                to_address: "treasury".to_string(),
+                amount: coins(1000, "uluna"),
+            })],
+            res.messages.into_iter().map(|m| m.msg).collect::<Vec<_>>()
+        );
+
+        let msg = QueryMsg::QueryBond {
+            address: "bob".to_string(),
+        };
+        let res: BondResponse =
+            from_binary(&query(deps.as_ref(), mock_env(), msg).unwrap()).unwrap();
+        assert_eq!(Uint128::new(600), res.bonded);
+        assert_eq!(0, res.strikes);
+        assert_eq!(Uint128::new(400), res.unbonding[0].amount);
+
+        let msg = QueryMsg::QuerySlashes {
+            start_after: None,
+            limit: None,
+        };
+        let res: SlashesResponse =
+            from_binary(&query(deps.as_ref(), mock_env(), msg).unwrap()).unwrap();
+        assert_eq!(1, res.slashes.len());
+        assert_eq!(Addr::unchecked("bob"), res.slashes[0].publisher);
+        assert_eq!(Uint128::new(1000), res.slashes[0].amount);
+
+        // Under the minimum bond now, and the unbonded stake is locked until the delay passes
+        let err =
+            execute(deps.as_mut(), mock_env(), mock_info("bob", &[]), update(10)).unwrap_err();
+        assert!(matches!(err, ContractError::InsufficientBond { .. }));
+        let withdraw = ExecuteMsg::WithdrawUnbonded {};
+        let err = execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("bob", &[]),
+            withdraw.clone(),
+        )
+        .unwrap_err();
+        assert!(matches!(err, ContractError::NothingToWithdraw {}));
+
+        let mut env = mock_env();
+        env.block.time = env.block.time.plus_seconds(100);
+        let res = execute(deps.as_mut(), env, mock_info("bob", &[]), withdraw).unwrap();
+        assert_eq!(
+            CosmosMsg::Bank(BankMsg::Send {
+                to_address: "bob".to_string(),
+                amount: coins(400, "uluna"),
+            }),
+            res.messages[0].msg
+        );
+
+        let msg = QueryMsg::QueryBonds {
+            start_after: None,
+            limit: None,
+        };
+        let res: BondsResponse =
+            from_binary(&query(deps.as_ref(), mock_env(), msg).unwrap()).unwrap();
+        let bonded: Vec<_> = res
+            .bonds
+            .iter()
+            .map(|b| (b.address.as_str(), b.bonded))
+            .collect();
+        assert_eq!(
+            vec![("alice", Uint128::new(1000)), ("bob", Uint128::new(600))],
+            bonded
+        );
+    }
+
+    #[test]
+    fn outliers_cannot_pull_their_own_reference() {
+        let mut deps = mock_dependencies(&[]);
+
+        let msg = InstantiateMsg {
+            price: Decimal::from_ratio(10u128, 1u128),
+            default_feed: aurm_feed(),
+            decimals: 6,
+            publishers: vec!["alice".to_string()],
+            max_price_age: Some(60),
+            history_size: None,
+            admin: None,
+        };
+        let admin = mock_info("creator", &[]);
+        let _res = instantiate(deps.as_mut(), mock_env(), admin.clone(), msg).unwrap();
+
+        let msg = ExecuteMsg::SetBondingConfig {
+            config: Some(BondingConfig {
+                min_bond: Uint128::new(1000),
+                max_deviation: Decimal::percent(50),
+                strikes_to_slash: 3,
+                slash_ratio: Decimal::percent(50),
+                treasury: "treasury".to_string(),
+                unbonding_seconds: 100,
+            }),
+        };
+        let _res = execute(deps.as_mut(), mock_env(), admin, msg).unwrap();
+        let info = mock_info("alice", &coins(1000, "uluna"));
+        let _res = execute(deps.as_mut(), mock_env(), info, ExecuteMsg::Bond {}).unwrap();
+
+        let update = |price: u128| ExecuteMsg::UpdatePrice {
+            feed: None,
+            price: Decimal::from_ratio(price, 1u128),
+        };
+        let strikes = |deps: &OwnedDeps<_, _, _>| {
+            let msg = QueryMsg::QueryBond {
+                address: "alice".to_string(),
+            };
+            let res: BondResponse =
+                from_binary(&query(deps.as_ref(), mock_env(), msg).unwrap()).unwrap();
+            res.strikes
+        };
+
+        // Within 50% of the median of 10 and 20, but twice the creator's 10
+        let info = mock_info("alice", &[]);
+        let _res = execute(deps.as_mut(), mock_env(), info.clone(), update(20)).unwrap();
+        assert_eq!(1, strikes(&deps));
+
+        // Once the creator's price is stale, alice is checked against the last
+        // observation, which is her own 10
+        let mut env = mock_env();
+        env.block.time = env.block.time.plus_seconds(61);
+        let _res = execute(deps.as_mut(), env.clone(), info.clone(), update(10)).unwrap();
+        assert_eq!(1, strikes(&deps));
+        let _res = execute(deps.as_mut(), env, info, update(20)).unwrap();
+        assert_eq!(2, strikes(&deps));
+    }
+
+    #[test]
+    fn honest_reveals_are_not_struck_with_the_outlier() {
+        let mut deps = mock_dependencies(&[]);
+
+        let msg = InstantiateMsg {
+            price: Decimal::from_ratio(10u128, 1u128),
+            default_feed: aurm_feed(),
+            decimals: 6,
+            publishers: vec!["alice".to_string(), "bob".to_string(), "carol".to_string()],
+            max_price_age: None,
+            history_size: None,
+            admin: None,
+        };
+        let admin = mock_info("creator", &[]);
+        let _res = instantiate(deps.as_mut(), mock_env(), admin.clone(), msg).unwrap();
+
+        let msg = ExecuteMsg::SetBondingConfig {
+            config: Some(BondingConfig {
+                min_bond: Uint128::new(1000),
+                max_deviation: Decimal::percent(20),
+                strikes_to_slash: 3,
+                slash_ratio: Decimal::percent(50),
+                treasury: "treasury".to_string(),
+                unbonding_seconds: 100,
+            }),
+        };
+        let _res = execute(deps.as_mut(), mock_env(), admin.clone(), msg).unwrap();
+        for publisher in ["alice", "bob", "carol"] {
+            let info = mock_info(publisher, &coins(1000, "uluna"));
+            let _res = execute(deps.as_mut(), mock_env(), info, ExecuteMsg::Bond {}).unwrap();
+        }
+        let msg = ExecuteMsg::UpdatePrice {
+            feed: None,
+            price: Decimal::from_ratio(10u128, 1u128),
+        };
+        let _res = execute(deps.as_mut(), mock_env(), mock_info("carol", &[]), msg).unwrap();
+
+        let msg = ExecuteMsg::SetRoundConfig {
+            feed: None,
+            config: Some(RoundConfig {
+                commit_seconds: 60,
+                reveal_seconds: 60,
+            }),
+        };
+        let _res = execute(deps.as_mut(), mock_env(), admin, msg).unwrap();
+        let msg = ExecuteMsg::StartRound { feed: None };
+        let _res = execute(deps.as_mut(), mock_env(), mock_info("alice", &[]), msg).unwrap();
+
+        // Only alice and bob take part, 20 is a third off their midpoint of 15
+        let prices = [("alice", "10"), ("bob", "20")];
+        for (publisher, price) in prices {
+            let msg = ExecuteMsg::CommitPrice {
+                feed: None,
+                commitment: commitment(publisher, 0, price, "salt"),
+            };
+            let _res = execute(deps.as_mut(), mock_env(), mock_info(publisher, &[]), msg).unwrap();
+        }
+        let mut env = mock_env();
+        env.block.time = env.block.time.plus_seconds(60);
+        for (publisher, price) in prices {
+            let msg = ExecuteMsg::RevealPrice {
+                feed: None,
+                price: price.parse().unwrap(),
+                salt: "salt".to_string(),
+            };
+            let _res = execute(deps.as_mut(), env.clone(), mock_info(publisher, &[]), msg).unwrap();
+        }
+        env.block.time = env.block.time.plus_seconds(60);
+        let msg = ExecuteMsg::FinalizeRound { feed: None };
+        let _res = execute(deps.as_mut(), env.clone(), mock_info("anyone", &[]), msg).unwrap();
+
+        // The creator's and carol's 10 side with alice against bob
+        for (publisher, expected) in [("alice", 0), ("bob", 1), ("carol", 0)] {
+            let msg = QueryMsg::QueryBond {
+                address: publisher.to_string(),
+            };
+            let res: BondResponse =
+                from_binary(&query(deps.as_ref(), env.clone(), msg).unwrap()).unwrap();
+            assert_eq!(expected, res.strikes, "{}", publisher);
+        }
+    }
+
+    #[test]
+    fn feed_health_follows_heartbeats() {
+        let mut deps = mock_dependencies(&[]);
+
+        let msg = InstantiateMsg {
+            price: Decimal::from_ratio(10u128, 1u128),
+            default_feed: aurm_feed(),
+            decimals: 6,
+            publishers: vec![],
+            max_price_age: None,
+            history_size: None,
+            admin: None,
+        };
+        let admin = mock_info("creator", &[]);
+        let _res = instantiate(deps.as_mut(), mock_env(), admin.clone(), msg).unwrap();
+
+        let native_feed = |denom: &str| FeedId {
+            asset: AssetId::Native {
+                denom: denom.to_string(),
+            },
+            quote_denom: "uluna".to_string(),
+        };
+        for denom in ["ukrw", "uusd"] {
+            let msg = ExecuteMsg::RegisterFeed {
+                feed: native_feed(denom),
+                decimals: 6,
+            };
+            let _res = execute(deps.as_mut(), mock_env(), admin.clone(), msg).unwrap();
+        }
+        let msg = ExecuteMsg::UpdatePrice {
+            feed: Some(native_feed("uusd")),
+            price: Decimal::percent(1),
+        };
+        let _res = execute(deps.as_mut(), mock_env(), admin.clone(), msg).unwrap();
+
+        let health = |deps: Deps, env: &Env, feed: FeedId| {
+            let msg = QueryMsg::QueryFeedHealth { feed: Some(feed) };
+            from_binary::<FeedHealthResponse>(&query(deps, env.clone(), msg).unwrap()).unwrap()
+        };
+        let res = health(deps.as_ref(), &mock_env(), aurm_feed());
+        assert_eq!(FeedStatus::Healthy, res.status);
+        assert_eq!(Some(mock_env().block.time), res.last_updated_time);
+        assert_eq!(1, res.active_publishers);
+        assert_eq!(
+            FeedStatus::Stale,
+            health(deps.as_ref(), &mock_env(), native_feed("ukrw")).status
+        );
+
+        // A missed heartbeat degrades the feed while its price is still served
+        let mut env = mock_env();
+        env.block.time = env.block.time.plus_seconds(DEFAULT_HEARTBEAT + 100);
+        let res = health(deps.as_ref(), &env, aurm_feed());
+        assert_eq!(FeedStatus::Degraded, res.status);
+        assert_eq!(100, res.seconds_overdue);
+
+        let msg = ExecuteMsg::SetHeartbeat {
+            feed: Some(native_feed("uusd")),
+            heartbeat_seconds: DEFAULT_HEARTBEAT * 2,
+        };
+        let _res = execute(deps.as_mut(), mock_env(), admin, msg).unwrap();
+        assert_eq!(
+            FeedStatus::Healthy,
+            health(deps.as_ref(), &env, native_feed("uusd")).status
+        );
+
+        let msg = QueryMsg::QueryFeedsByStaleness {
+            start_after: None,
+            limit: None,
+        };
+        let res: FeedHealthsResponse =
+            from_binary(&query(deps.as_ref(), env.clone(), msg).unwrap()).unwrap();
+        let feeds: Vec<_> = res.feeds.into_iter().map(|health| health.feed).collect();
+        assert_eq!(
+            vec![native_feed("ukrw"), aurm_feed(), native_feed("uusd")],
+            feeds
+        );
+
+        let msg = QueryMsg::QueryFeedsByStaleness {
+            start_after: Some(native_feed("ukrw")),
+            limit: Some(1),
+        };
+        let res: FeedHealthsResponse =
+            from_binary(&query(deps.as_ref(), env.clone(), msg).unwrap()).unwrap();
+        assert_eq!(1, res.feeds.len());
+        assert_eq!(aurm_feed(), res.feeds[0].feed);
+
+        // Without fresh submissions the feed is stale
+        env.block.time = env.block.time.plus_seconds(DEFAULT_MAX_PRICE_AGE);
+        let res = health(deps.as_ref(), &env, aurm_feed());
+        assert_eq!(FeedStatus::Stale, res.status);
+        assert_eq!(0, res.active_publishers);
+    }
+
+    #[test]
+    fn batch_update_covers_several_feeds() {
+        let mut deps = mock_dependencies(&[]);
+
+        let msg = InstantiateMsg {
+            price: Decimal::from_ratio(10u128, 1u128),
+            default_feed: aurm_feed(),
+            decimals: 6,
+            publishers: vec![],
+            max_price_age: None,
+            history_size: None,
+            admin: None,
+        };
+        let admin = mock_info("creator", &[]);
+        let _res = instantiate(deps.as_mut(), mock_env(), admin.clone(), msg).unwrap();
+
+        let usd_feed = FeedId {
+            asset: AssetId::Native {
+                denom: "uusd".to_string(),
+            },
+            quote_denom: "uluna".to_string(),
+        };
+        let msg = ExecuteMsg::RegisterFeed {
+            feed: usd_feed.clone(),
+            decimals: 2,
+        };
+        let _res = execute(deps.as_mut(), mock_env(), admin.clone(), msg).unwrap();
+
+        let msg = ExecuteMsg::BatchUpdate {
+            updates: vec![
+                (aurm_feed(), Decimal::from_ratio(12u128, 1u128)),
+                (usd_feed.clone(), Decimal::percent(1)),
+            ],
+        };
+        let err = execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("anyone", &[]),
+            msg.clone(),
+        )
+        .unwrap_err();
+        assert!(matches!(
+            err,
+            ContractError::Access(AccessError::Unauthorized {})
+        ));
+
+        let res = execute(deps.as_mut(), mock_env(), admin.clone(), msg).unwrap();
+        assert_eq!(2, res.events.len());
+        assert_eq!("price_update", res.events[1].ty);
+        assert!(res.events[1]
+            .attributes
+            .contains(&Attribute::new("feed", feed_label(&usd_feed))));
+
+        for (feed, price) in [
+            (aurm_feed(), Decimal::from_ratio(12u128, 1u128)),
+            (usd_feed.clone(), Decimal::percent(1)),
+        ] {
+            let msg = QueryMsg::QueryPriceV2 {
+                feed: Some(feed),
+                max_age_seconds: None,
+                kind: None,
+            };
+            let res: PriceResponseV2 =
+                from_binary(&query(deps.as_ref(), mock_env(), msg).unwrap()).unwrap();
+            assert_eq!(price, res.price);
+        }
+
+        // One bad entry fails the whole batch
+        let msg = ExecuteMsg::BatchUpdate {
+            updates: vec![
+                (aurm_feed(), Decimal::from_ratio(13u128, 1u128)),
+                (usd_feed.clone(), Decimal::permille(1)),
+            ],
+        };
+        let err = execute(deps.as_mut(), mock_env(), admin.clone(), msg).unwrap_err();
+        assert!(matches!(err, ContractError::PricePrecision { decimals: 2 }));
+
+        let msg = ExecuteMsg::BatchUpdate {
+            updates: vec![
+                (usd_feed.clone(), Decimal::percent(2)),
+                (usd_feed, Decimal::percent(3)),
+            ],
+        };
+        let err = execute(deps.as_mut(), mock_env(), admin.clone(), msg).unwrap_err();
+        assert!(matches!(err, ContractError::DuplicateBatchFeed { .. }));
+
+        let msg = ExecuteMsg::BatchUpdate { updates: vec![] };
+        let err = execute(deps.as_mut(), mock_env(), admin, msg).unwrap_err();
+        assert!(matches!(err, ContractError::EmptyBatch {}));
+    }
+
+    #[test]
+    fn rate_limit_spaces_out_updates() {
+        let mut deps = mock_dependencies(&[]);
+
+        let msg = InstantiateMsg {
+            price: Decimal::from_ratio(10u128, 1u128),
+            default_feed: aurm_feed(),
+            decimals: 6,
+            publishers: vec![],
+            max_price_age: None,
+            history_size: None,
+            admin: None,
+        };
+        let admin = mock_info("creator", &[]);
+        let _res = instantiate(deps.as_mut(), mock_env(), admin.clone(), msg).unwrap();
+
+        let msg = ExecuteMsg::SetRateLimit {
+            feed: None,
+            rate_limit: Some(RateLimit {
+                min_interval: 60,
+                override_deviation: Some(Decimal::percent(5)),
+            }),
+        };
+        let _res = execute(deps.as_mut(), mock_env(), admin.clone(), msg).unwrap();
+
+        let update = |price: Decimal| ExecuteMsg::UpdatePrice { feed: None, price };
+        let at = |seconds: u64| {
+            let mut env = mock_env();
+            env.block.time = env.block.time.plus_seconds(seconds);
+            env
+        };
+
+        let err = execute(
+            deps.as_mut(),
+            at(10),
+            admin.clone(),
+            update(Decimal::from_str("10.2").unwrap()),
+        )
+        .unwrap_err();
+        assert!(matches!(
+            err,
+            ContractError::UpdateTooSoon { seconds_left: 50 }
+        ));
+
+        // A large enough move goes through early, and restarts the interval
+        let _res = execute(
+            deps.as_mut(),
+            at(10),
+            admin.clone(),
+            update(Decimal::from_ratio(11u128, 1u128)),
+        )
+        .unwrap();
+        let err = execute(
+            deps.as_mut(),
+            at(30),
+            admin.clone(),
+            update(Decimal::from_str("11.1").unwrap()),
+        )
+        .unwrap_err();
+        assert!(matches!(
+            err,
+            ContractError::UpdateTooSoon { seconds_left: 40 }
+        ));
+
+        let _res = execute(
+            deps.as_mut(),
+            at(70),
+            admin,
+            update(Decimal::from_str("11.1").unwrap()),
+        )
+        .unwrap();
+    }
+
+    // Answers smart queries with the oracle instantiated in the wrapped deps
+    struct OracleQuerier(OwnedDeps<MockStorage, MockApi, MockQuerier>);
+
+    impl Querier for OracleQuerier {
+        fn raw_query(&self, bin_request: &[u8]) -> QuerierResult {
+            match from_slice(bin_request).unwrap() {
+                QueryRequest::<Empty>::Wasm(WasmQuery::Smart { msg, .. }) => {
+                    let msg = from_binary(&msg).unwrap();
+                    SystemResult::Ok(query(self.0.as_ref(), mock_env(), msg).into())
+                }
+                request => panic!("unexpected query {:?}", request),
+            }
+        }
+    }
+
+    #[test]
+    fn shared_client_matches_oracle_messages() {
+        let oracle = OracleContract(Addr::unchecked("oracle"));
+        let executed = |msg: CosmosMsg| match msg {
+            CosmosMsg::Wasm(WasmMsg::Execute {
+                contract_addr, msg, ..
+            }) => {
+                assert_eq!("oracle", contract_addr);
+                from_binary::<ExecuteMsg>(&msg).unwrap()
+            }
+            msg => panic!("unexpected message {:?}", msg),
+        };
+
+        let msg = oracle
+            .update_price(Some(aurm_feed()), Decimal::percent(150))
+            .unwrap();
+        assert_eq!(
+            ExecuteMsg::UpdatePrice {
+                feed: Some(aurm_feed()),
+                price: Decimal::percent(150),
+            },
+            executed(msg)
+        );
+        let updates = vec![(aurm_feed(), Decimal::percent(150))];
+        let msg = oracle.batch_update(updates.clone()).unwrap();
+        assert_eq!(ExecuteMsg::BatchUpdate { updates }, executed(msg));
+
+        // The client's price query is answered by the oracle itself
+        let mut deps = mock_dependencies(&[]);
+        let msg = InstantiateMsg {
+            price: Decimal::from_ratio(10u128, 1u128),
+            default_feed: aurm_feed(),
+            decimals: 6,
+            publishers: vec![],
+            max_price_age: None,
+            history_size: None,
+            admin: None,
+        };
+        let _res = instantiate(deps.as_mut(), mock_env(), mock_info("creator", &[]), msg).unwrap();
+
+        let querier = OracleQuerier(deps);
+        let res = oracle
+            .query_price(
+                &QuerierWrapper::new(&querier),
+                None,
+                None,
+                Some(PriceKind::Ema),
+            )
+            .unwrap();
+        assert_eq!(Decimal::from_ratio(10u128, 1u128), res.price);
     }
 }
@@ -1,4 +1,6 @@
-use cosmwasm_std::StdError;
+use cosmwasm_std::{Decimal, StdError, Uint128};
+use cw0::PaymentError;
+use shared::access::AccessError;
 use thiserror::Error;
 
 #[derive(Error, Debug)]
@@ -6,10 +8,129 @@ pub enum ContractError {
     #[error("{0}")]
     Std(#[from] StdError),
 
-    #[error("Unauthorized")]
-    Unauthorized {},
+    #[error("{0}")]
+    Payment(#[from] PaymentError),
+
+    #[error("{0}")]
+    Access(#[from] AccessError),
 
     #[error("Price has to be positive.")]
-    PriceInstantiationError {}, // Add any other custom errors you like here.
-                                // Look at https://docs.rs/thiserror/1.0.21/thiserror/ for details.
+    PriceInstantiationError {},
+    // Add any other custom errors you like here.
+    // Look at https://docs.rs/thiserror/1.0.21/thiserror/ for details.
+    #[error("Strikes to slash must be positive and the slash ratio at most 1")]
+    InvalidBondingConfig {},
+
+    #[error("Bond of {bonded} uluna is below the required {min_bond} uluna")]
+    InsufficientBond { bonded: Uint128, min_bond: Uint128 },
+
+    #[error("Cannot unbond more than the bonded {bonded} uluna")]
+    UnbondTooLarge { bonded: Uint128 },
+
+    #[error("No unbonded stake is ready to withdraw")]
+    NothingToWithdraw {},
+
+    #[error("Oracle is paused")]
+    Paused {},
+
+    #[error("{address} is not an approved publisher")]
+    PublisherNotFound { address: String },
+
+    #[error("Feed {feed} is already registered")]
+    FeedAlreadyExists { feed: String },
+
+    #[error("The default feed cannot be removed")]
+    DefaultFeedRemoval {},
+
+    #[error("Feeds support at most 18 decimals, got {decimals}")]
+    InvalidDecimals { decimals: u8 },
+
+    #[error("Price has more than the {decimals} decimals of its feed")]
+    PricePrecision { decimals: u8 },
+
+    #[error("Price {price} is below the feed minimum of {min_price}")]
+    PriceBelowMinimum { price: Decimal, min_price: Decimal },
+
+    #[error("Price {price} is above the feed maximum of {max_price}")]
+    PriceAboveMaximum { price: Decimal, max_price: Decimal },
+
+    #[error("Price {price} moves more than {max_change} away from {last_price}")]
+    PriceDeviation {
+        price: Decimal,
+        last_price: Decimal,
+        max_change: Decimal,
+    },
+
+    #[error("EMA half-life must be longer than zero seconds")]
+    InvalidHalfLife {},
+
+    #[error("Batch update has no entries")]
+    EmptyBatch {},
+
+    #[error("Feed {feed} appears more than once in the batch")]
+    DuplicateBatchFeed { feed: String },
+
+    #[error("Rate limit interval must be longer than zero seconds")]
+    InvalidRateLimit {},
+
+    #[error("Next update allowed in {seconds_left} seconds")]
+    UpdateTooSoon { seconds_left: u64 },
+
+    #[error("Heartbeat must be longer than zero seconds")]
+    InvalidHeartbeat {},
+
+    #[error("No pending price from {publisher}")]
+    NoPendingPrice { publisher: String },
+
+    #[error("{signer} is not a registered signer")]
+    UnknownSigner { signer: String },
+
+    #[error("Public key must be a 33 or 65 byte secp256k1 key")]
+    InvalidPublicKey {},
+
+    #[error("Invalid attestation signature")]
+    InvalidSignature {},
+
+    #[error("Attestation at {timestamp} with nonce {nonce} is not newer than the last one")]
+    AttestationReplay { timestamp: u64, nonce: u64 },
+
+    #[error("Attestation timestamp {timestamp} is in the future")]
+    AttestationFromFuture { timestamp: u64 },
+
+    #[error("Attestation is meant for oracle {oracle} on {chain_id}")]
+    AttestationWrongDomain { oracle: String, chain_id: String },
+
+    #[error("Round windows must be longer than zero seconds")]
+    InvalidRoundConfig {},
+
+    #[error("Feed {feed} takes prices through commit-reveal rounds")]
+    RoundBasedFeed { feed: String },
+
+    #[error("Feed {feed} has no commit-reveal rounds")]
+    NoRounds { feed: String },
+
+    #[error("Round {round} is in its {phase} phase")]
+    WrongRoundPhase { round: u64, phase: String },
+
+    #[error("No commitment from {publisher} in the current round")]
+    NoCommitment { publisher: String },
+
+    #[error("Revealed price and salt do not match the commitment")]
+    CommitmentMismatch {},
+
+    #[error("Native references need a native asset, or another feed of the same asset")]
+    InvalidNativeReference {},
+
+    #[error("Price {price} is more than {tolerance} away from the native reference {reference}")]
+    NativeReferenceMismatch {
+        price: Decimal,
+        reference: Decimal,
+        tolerance: Decimal,
+    },
+
+    #[error("{subscriber} is already subscribed to feed {feed}")]
+    AlreadySubscribed { subscriber: String, feed: String },
+
+    #[error("{subscriber} is not subscribed to feed {feed}")]
+    SubscriptionNotFound { subscriber: String, feed: String },
 }
@@ -1,26 +1,180 @@
+use cosmwasm_std::{Addr, Binary, Decimal, Timestamp, Uint128};
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 
-#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
-pub struct InstantiateMsg {
-    pub price: u64,
+use crate::state::{Bonding, Observation, Round, Slash, Submission, Unbonding};
+
+// The messages are defined in shared, next to the client sending them
+pub use shared::oracle::{
+    AssetId, Attestation, BondingConfig, ExecuteMsg, FeedId, HookMsg, InstantiateMsg,
+    LegacyPriceResponse as PriceResponse, NativeReference, NativeSource, PriceBounds, PriceKind,
+    PriceResponse as PriceResponseV2, QueryMsg, RateLimit, RoundConfig, GUARDIAN_ROLE,
+};
+
+// Health of a feed: healthy while updated within its heartbeat, degraded once
+// an update is overdue, and stale when no publisher has a fresh price so the
+// feed can't be queried at all
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum FeedStatus {
+    Healthy,
+    Degraded,
+    Stale,
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 #[serde(rename_all = "snake_case")]
-pub enum ExecuteMsg {
-    UpdatePrice { price: u64 },
+pub enum RoundPhase {
+    Commit,
+    Reveal,
+    // The reveal window is over and anyone can finalize the round
+    Ended,
+    Finalized,
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
-#[serde(rename_all = "snake_case")]
-pub enum QueryMsg {
-    // GetCount returns the price in LUNA as a json-encoded number
-    QueryPrice {},
+pub struct PriceHistoryResponse {
+    pub observations: Vec<Observation>,
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
-#[serde(rename_all = "snake_case")]
-pub struct PriceResponse {
-    pub price: u64,
+pub struct EmaResponse {
+    pub ema: Decimal,
+    pub spot: Decimal,
+    pub quote_denom: String,
+    pub half_life_seconds: u64,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct TwapResponse {
+    pub price: Decimal,
+    pub quote_denom: String,
+    pub window_seconds: u64,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct FeedResponse {
+    pub id: FeedId,
+    pub decimals: u8,
+    pub bounds: PriceBounds,
+    pub rounds: Option<RoundConfig>,
+    pub native_reference: Option<NativeReference>,
+    pub ema_half_life: u64,
+    pub heartbeat: u64,
+    pub rate_limit: Option<RateLimit>,
+    // None when the feed has no fresh submissions
+    pub price: Option<PriceResponseV2>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct FeedsResponse {
+    pub feeds: Vec<FeedResponse>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct FeedHealthResponse {
+    pub feed: FeedId,
+    pub status: FeedStatus,
+    pub heartbeat: u64,
+    // None if the feed was never updated
+    pub last_updated_time: Option<Timestamp>,
+    // Seconds past the heartbeat since the last update
+    pub seconds_overdue: u64,
+    // Publishers with a submission younger than the max price age
+    pub active_publishers: u32,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct FeedHealthsResponse {
+    pub feeds: Vec<FeedHealthResponse>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct PendingPriceResponse {
+    pub publisher: Addr,
+    pub submission: Submission,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct PendingPricesResponse {
+    pub pending: Vec<PendingPriceResponse>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct PublisherResponse {
+    pub address: Addr,
+    pub last_submission: Option<Submission>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct PublishersResponse {
+    pub publishers: Vec<PublisherResponse>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct SignerResponse {
+    pub address: Addr,
+    pub public_key: Binary,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct SignersResponse {
+    pub signers: Vec<SignerResponse>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct RoundResponse {
+    pub round: Round,
+    pub phase: RoundPhase,
+    // Publishers that have revealed so far
+    pub reveals: u32,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct RoundsResponse {
+    pub rounds: Vec<RoundResponse>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct SubscriberResponse {
+    pub address: Addr,
+    pub approved: bool,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct SubscribersResponse {
+    pub subscribers: Vec<SubscriberResponse>,
+}
+
+// Publishers and signers are listed by QueryPublishers and QuerySigners
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct RolesResponse {
+    // None once the admin role has been renounced
+    pub admin: Option<Addr>,
+    pub pending_admin: Option<Addr>,
+    pub paused: bool,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct BondingConfigResponse {
+    // None while bonding is not required
+    pub config: Option<Bonding>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct BondResponse {
+    pub address: Addr,
+    pub bonded: Uint128,
+    pub strikes: u32,
+    pub unbonding: Vec<Unbonding>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct BondsResponse {
+    pub bonds: Vec<BondResponse>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct SlashesResponse {
+    pub slashes: Vec<Slash>,
 }
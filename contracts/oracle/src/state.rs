@@ -1,13 +1,183 @@
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 
-use cosmwasm_std::Addr;
-use cw_storage_plus::Item;
+use cosmwasm_std::{Addr, Binary, Decimal, Empty, Timestamp, Uint128};
+use cw_storage_plus::{Item, Map, PrimaryKey, U64Key};
+
+use crate::msg::{AssetId, FeedId, NativeReference, PriceBounds, RateLimit, RoundConfig};
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 pub struct State {
-    pub price: u64,
-    pub owner: Addr,
+    pub paused: bool,
+    // None while publishers don't have to bond
+    pub bonding: Option<Bonding>,
+    // Slashes so far, the next one gets this id
+    pub slash_count: u64,
+    // Submissions older than this many seconds are left out of the median
+    pub max_price_age: u64,
+    pub default_feed: FeedId,
+    // Number of observations, and of rounds, kept per feed before the oldest
+    // is overwritten
+    pub history_size: u32,
+}
+
+// Validated `BondingConfig`
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct Bonding {
+    pub min_bond: Uint128,
+    pub max_deviation: Decimal,
+    pub strikes_to_slash: u32,
+    pub slash_ratio: Decimal,
+    pub treasury: Addr,
+    pub unbonding_seconds: u64,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq, JsonSchema)]
+pub struct Bond {
+    pub amount: Uint128,
+    // Outlier submissions since the last slash
+    pub strikes: u32,
+    pub unbonding: Vec<Unbonding>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct Unbonding {
+    pub amount: Uint128,
+    pub release_at: Timestamp,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct Slash {
+    pub id: u64,
+    pub publisher: Addr,
+    pub amount: Uint128,
+    pub block_height: u64,
+    pub block_time: Timestamp,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct Feed {
+    pub id: FeedId,
+    // Number of fractional digits prices on this feed are published with
+    pub decimals: u8,
+    // Observations recorded so far, the next one gets this sequence number
+    pub history_count: u64,
+    pub bounds: PriceBounds,
+    pub rounds: Option<RoundConfig>,
+    // Rounds started so far, the current one has id `round_count - 1`
+    pub round_count: u64,
+    pub native_reference: Option<NativeReference>,
+    pub ema_half_life: u64,
+    // Longest expected gap between updates, in seconds
+    pub heartbeat: u64,
+    pub rate_limit: Option<RateLimit>,
+    // None until the first observation
+    pub ema: Option<Ema>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct Submission {
+    pub price: Decimal,
+    pub block_height: u64,
+    pub block_time: Timestamp,
+}
+
+// Exponential moving average of a feed's price as of `block_time`
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct Ema {
+    pub price: Decimal,
+    pub block_time: Timestamp,
+}
+
+// Aggregated price of a feed right after an update
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct Observation {
+    pub sequence: u64,
+    pub price: Decimal,
+    pub block_height: u64,
+    pub block_time: Timestamp,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct Round {
+    pub id: u64,
+    pub commit_end: Timestamp,
+    pub reveal_end: Timestamp,
+    // Median of the revealed prices, None until finalized or if nobody revealed
+    pub price: Option<Decimal>,
+    pub finalized: bool,
+}
+
+// A publisher's commitment in a round, and its price once revealed
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct Commitment {
+    pub round: u64,
+    pub hash: Binary,
+    pub revealed: Option<Decimal>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct Subscription {
+    // Hooks are only sent once the admin has approved the subscription
+    pub approved: bool,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct Signer {
+    // secp256k1 key the signer's attestations are checked against
+    pub public_key: Binary,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct LastAttestation {
+    pub timestamp: u64,
+    pub nonce: u64,
 }
 
 pub const STATE: Item<State> = Item::new("state");
+
+// Registered feeds, keyed by `feed_key`
+pub const FEEDS: Map<&[u8], Feed> = Map::new("feeds");
+
+// Approved publishers, managed by the admin
+pub const PUBLISHERS: Map<&Addr, Empty> = Map::new("publishers");
+
+// Latest submission of each publisher, per feed
+pub const SUBMISSIONS: Map<(&[u8], &Addr), Submission> = Map::new("submissions");
+
+// Off-chain signers whose attestations anyone may relay, managed by the admin
+pub const SIGNERS: Map<&Addr, Signer> = Map::new("signers");
+
+// Newest attestation accepted per feed and signer, guards against replays
+pub const LAST_ATTESTATIONS: Map<(&[u8], &Addr), LastAttestation> = Map::new("last_attestations");
+
+// Stake bonded by publishers, in uluna
+pub const BONDS: Map<&Addr, Bond> = Map::new("bonds");
+
+// Every slash ever made, by id
+pub const SLASHES: Map<U64Key, Slash> = Map::new("slashes");
+
+// Contracts notified of price updates, per feed
+pub const SUBSCRIBERS: Map<(&[u8], &Addr), Subscription> = Map::new("subscribers");
+
+// Out-of-bound submissions waiting for the admin, per feed and publisher
+pub const PENDING: Map<(&[u8], &Addr), Submission> = Map::new("pending");
+
+// Ring buffer of observations per feed, slot is `sequence % history_size`
+pub const HISTORY: Map<(&[u8], U64Key), Observation> = Map::new("history");
+
+// Ring buffer of rounds per feed, slot is `id % history_size`
+pub const ROUNDS: Map<(&[u8], U64Key), Round> = Map::new("rounds");
+
+// Commitments for the current round, per feed and publisher
+pub const COMMITMENTS: Map<(&[u8], &Addr), Commitment> = Map::new("commitments");
+
+// Storage key of a feed. Asset and quote denom are length-prefixed so that
+// denoms containing separators can never collide.
+pub fn feed_key(feed: &FeedId) -> Vec<u8> {
+    let asset = match &feed.asset {
+        AssetId::Cw20 { contract_addr } => format!("cw20:{}", contract_addr),
+        AssetId::Native { denom } => format!("native:{}", denom),
+    };
+    (asset.as_str(), feed.quote_denom.as_str()).joined_key()
+}
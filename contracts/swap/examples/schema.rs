@@ -3,7 +3,11 @@ use std::fs::create_dir_all;
 
 use cosmwasm_schema::{export_schema, remove_schemas, schema_for};
 
-use swap::msg::{BalanceResponse, ExecuteMsg, InstantiateMsg, QueryMsg, TokenAddrResponse};
+use swap::msg::{
+    AssetsResponse, BalanceResponse, ExecuteMsg, InstantiateMsg, QueryMsg, ReceiveMsg,
+    TokenAddrResponse,
+};
+
 fn main() {
     let mut out_dir = current_dir().unwrap();
     out_dir.push("schema");
@@ -12,7 +16,9 @@ fn main() {
 
     export_schema(&schema_for!(InstantiateMsg), &out_dir);
     export_schema(&schema_for!(ExecuteMsg), &out_dir);
+    export_schema(&schema_for!(ReceiveMsg), &out_dir);
     export_schema(&schema_for!(QueryMsg), &out_dir);
     export_schema(&schema_for!(BalanceResponse), &out_dir);
     export_schema(&schema_for!(TokenAddrResponse), &out_dir);
+    export_schema(&schema_for!(AssetsResponse), &out_dir);
 }
@@ -1,17 +1,26 @@
 #[cfg(not(feature = "library"))]
 use cosmwasm_std::entry_point;
 use cosmwasm_std::{
-    coin, to_binary, BankMsg, Binary, CosmosMsg, Deps, DepsMut, Empty, Env, MessageInfo,
-    QueryRequest, Response, StdError, StdResult, Uint128, WasmMsg, WasmQuery,
+    from_binary, to_binary, Addr, Binary, Decimal, Deps, DepsMut, Empty, Env, MessageInfo,
+    Response, StdError, StdResult, Uint128,
 };
 
 use cw2::set_contract_version;
-use cw20::{BalanceResponse as cw20_BalanceResponse, Cw20ExecuteMsg, Cw20QueryMsg};
-use oracle::msg::PriceResponse;
+use cw20::Cw20ReceiveMsg;
+use shared::access;
+use shared::asset::{
+    must_pay_native, must_receive_cw20, query_balance as query_asset_balance, query_decimals,
+    transfer_msg, Asset, AssetInfo,
+};
+use shared::math::{div_price, Rounding};
+use shared::oracle::OracleContract;
 
 use crate::error::ContractError;
-use crate::msg::{BalanceResponse, ExecuteMsg, InstantiateMsg, QueryMsg, TokenAddrResponse};
-use crate::state::{State, STATE};
+use crate::msg::{
+    AssetsResponse, BalanceResponse, ExecuteMsg, InstantiateMsg, QueryMsg, ReceiveMsg,
+    TokenAddrResponse,
+};
+use crate::state::{State, LEGACY_STATE, STATE};
 
 // version info for migration info
 const CONTRACT_NAME: &str = "crates.io:swap";
@@ -25,16 +34,27 @@ pub fn instantiate(
     msg: InstantiateMsg,
 ) -> Result<Response, ContractError> {
     set_contract_version(deps.storage, CONTRACT_NAME, CONTRACT_VERSION)?;
+    let offer_asset = msg.offer_asset.check(deps.api)?;
+    let ask_asset = msg.ask_asset.check(deps.api)?;
+    if offer_asset == ask_asset {
+        return Err(ContractError::SameAssets {});
+    }
+
     let state = State {
-        owner: info.sender.clone(),
         oracle_address: msg.oracle_address.clone(),
-        token_address: msg.token_address.clone(),
+        offer_decimals: query_decimals(&deps.querier, &offer_asset)?,
+        ask_decimals: query_decimals(&deps.querier, &ask_asset)?,
+        offer_asset,
+        ask_asset,
+        feed: msg.feed,
     };
     STATE.save(deps.storage, &state)?;
+    access::initialize_owner(deps.storage, &info.sender)?;
     Ok(Response::new()
         .add_attribute("method", "instantiate")
         .add_attribute("owner", &info.sender)
-        .add_attribute("token_address", &msg.token_address))
+        .add_attribute("offer_asset", state.offer_asset.to_string())
+        .add_attribute("ask_asset", state.ask_asset.to_string()))
 }
 
 #[cfg_attr(not(feature = "library"), entry_point)]
@@ -45,115 +65,135 @@ pub fn execute(
     msg: ExecuteMsg,
 ) -> Result<Response, ContractError> {
     match msg {
-        ExecuteMsg::Buy {} => try_buy(deps, info, env),
+        ExecuteMsg::Buy {} => {
+            let state = STATE.load(deps.storage)?;
+            let offer = must_pay_native(&info, &state.offer_asset)?;
+            try_buy(deps, env, info.sender, offer)
+        }
+        ExecuteMsg::Receive(receive) => try_receive(deps, env, info, receive),
         ExecuteMsg::Withdraw { amount } => try_withdraw(deps, info, env, amount),
+        ExecuteMsg::Access(msg) => Ok(access::execute(deps, info, msg)?),
     }
 }
 
-pub fn try_buy(deps: DepsMut, info: MessageInfo, env: Env) -> Result<Response, ContractError> {
-    if info.funds.len() != 1 {
-        return Err(StdError::GenericErr {
-            msg: "Didn't send any funds".to_string(),
-        }
-        .into());
+pub fn try_receive(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    receive: Cw20ReceiveMsg,
+) -> Result<Response, ContractError> {
+    let state = STATE.load(deps.storage)?;
+    let offer = must_receive_cw20(&info, &receive, &state.offer_asset)?;
+    let buyer = deps.api.addr_validate(&receive.sender)?;
+    match from_binary(&receive.msg)? {
+        ReceiveMsg::Buy {} => try_buy(deps, env, buyer, offer),
     }
+}
 
-    if info.funds[0].denom != "uluna".to_string() || info.funds[0].amount.is_zero() {
-        return Err(StdError::GenericErr {
-            msg: "Didn't send uluna".to_string(),
-        }
-        .into());
+pub fn try_buy(
+    deps: DepsMut,
+    env: Env,
+    buyer: Addr,
+    offer: Asset,
+) -> Result<Response, ContractError> {
+    let state = STATE.load(deps.storage)?;
+    let price = query_oracle(deps.as_ref())?;
+    if price.is_zero() {
+        return Err(StdError::generic_err("Oracle price is zero").into());
     }
 
-    let uluna_sent = info.funds[0].amount.u128();
-    let price_in_luna = query_oracle(deps.as_ref())? as u128;
-    let current_aurm_balance = query_balance_aurm(deps.as_ref(), env)?.u128();
-
-    let num_potential_swapped_aurm = uluna_sent / price_in_luna;
-    if num_potential_swapped_aurm > current_aurm_balance {
-        return Err(StdError::GenericErr {
-            msg: "Don't have enough AURM to swap".to_string(),
-        }
+    // offer / price in whole tokens, rounded down in favour of the contract
+    let ask_amount = div_price(
+        offer.amount,
+        state.offer_decimals,
+        price,
+        state.ask_decimals,
+        Rounding::Floor,
+    )?;
+    if ask_amount.is_zero() {
+        return Err(ContractError::OfferTooSmall {
+            offer: offer.to_string(),
+            ask_asset: state.ask_asset.to_string(),
+        });
+    }
+    let available = query_asset_balance(&deps.querier, &state.ask_asset, &env.contract.address)?;
+    if ask_amount > available {
+        return Err(StdError::generic_err(format!(
+            "Don't have enough {} to swap",
+            state.ask_asset
+        ))
         .into());
     }
 
-    let token_addr = STATE.load(deps.storage)?.token_address;
-    let msg = Cw20ExecuteMsg::Transfer {
-        recipient: info.sender.to_string(),
-        amount: Uint128::from(num_potential_swapped_aurm),
-    };
+    let ask = Asset::new(state.ask_asset, ask_amount);
+    let transfer = transfer_msg(&ask, &buyer)?;
 
     Ok(Response::new()
         .add_attribute("method", "try_buy")
-        .add_attribute("swapped_uluna", uluna_sent.to_string())
-        .add_attribute("swapped_aurm", num_potential_swapped_aurm.to_string())
-        .add_message(CosmosMsg::Wasm(WasmMsg::Execute {
-            contract_addr: token_addr.to_string(),
-            msg: to_binary(&msg)?,
-            funds: vec![],
-        })))
+        .add_attribute("offer", offer.to_string())
+        .add_attribute("ask", ask.to_string())
+        .add_message(transfer))
 }
 
-pub fn query_oracle(deps: Deps) -> Result<u64, ContractError> {
-    let State { oracle_address, .. } = STATE.load(deps.storage)?;
-    let resp: PriceResponse = deps.querier.query(&QueryRequest::Wasm(WasmQuery::Smart {
-        contract_addr: oracle_address.to_string(),
-        msg: to_binary(&oracle::msg::QueryMsg::QueryPrice {})?,
-    }))?;
+pub fn query_oracle(deps: Deps) -> Result<Decimal, ContractError> {
+    let State {
+        oracle_address,
+        feed,
+        ..
+    } = STATE.load(deps.storage)?;
+    let resp = OracleContract(oracle_address).query_price(&deps.querier, feed, None, None)?;
     Ok(resp.price)
 }
 
-pub fn query_balance_aurm(deps: Deps, env: Env) -> Result<Uint128, ContractError> {
-    let State { token_address, .. } = STATE.load(deps.storage)?;
-
-    let resp: cw20_BalanceResponse = deps.querier.query(&QueryRequest::Wasm(WasmQuery::Smart {
-        contract_addr: token_address.to_string(),
-        msg: to_binary(&Cw20QueryMsg::Balance {
-            address: env.contract.address.to_string(),
-        })?,
-    }))?;
-
-    Ok(resp.balance)
-}
-
 pub fn try_withdraw(
     deps: DepsMut,
     info: MessageInfo,
     env: Env,
     amount: i32,
 ) -> Result<Response, ContractError> {
+    access::assert_owner(deps.storage, &info.sender)?;
     let state = STATE.load(deps.storage)?;
-    if info.sender != state.owner {
-        return Err(ContractError::Unauthorized {});
-    }
-
-    let self_balance = deps
-        .querier
-        .query_balance(env.contract.address, String::from("uluna"))?;
 
-    if self_balance.amount.u128() < amount as u128 {
+    let self_balance =
+        query_asset_balance(&deps.querier, &state.offer_asset, &env.contract.address)?;
+    if self_balance.u128() < amount as u128 {
         return Err(StdError::GenericErr {
             msg: "Insufficient funds".to_string(),
         }
         .into());
     }
 
-    let msg = BankMsg::Send {
-        to_address: info.sender.to_string(),
-        amount: vec![coin(amount as u128, String::from("uluna"))],
-    };
+    let withdrawn = Asset::new(state.offer_asset, Uint128::from(amount as u128));
+    let msg = transfer_msg(&withdrawn, &info.sender)?;
 
     Ok(Response::new()
         .add_attribute("method", "try_withdraw")
         .add_attribute("amount_transferred", amount.to_string())
-        .add_attribute("denom_transferred", self_balance.denom.clone())
-        .add_message(CosmosMsg::Bank(msg)))
+        .add_attribute("asset_transferred", withdrawn.info.to_string())
+        .add_message(msg))
 }
 
 #[cfg_attr(not(feature = "library"), entry_point)]
-pub fn migrate(_deps: DepsMut, _env: Env, _msg: Empty) -> StdResult<Response> {
-    // TODO
-    Ok(Response::default())
+pub fn migrate(deps: DepsMut, _env: Env, _msg: Empty) -> Result<Response, ContractError> {
+    // Contracts instantiated before shared::access kept their owner in STATE
+    if access::OWNER.may_load(deps.storage)?.is_none() {
+        let legacy = LEGACY_STATE.load(deps.storage)?;
+        let offer_asset = AssetInfo::Native("uluna".to_string());
+        let ask_asset = AssetInfo::Cw20(legacy.token_address);
+        let state = State {
+            oracle_address: legacy.oracle_address,
+            offer_decimals: query_decimals(&deps.querier, &offer_asset)?,
+            ask_decimals: query_decimals(&deps.querier, &ask_asset)?,
+            offer_asset,
+            ask_asset,
+            feed: None,
+        };
+        STATE.save(deps.storage, &state)?;
+        access::initialize_owner(deps.storage, &legacy.owner)?;
+    }
+    set_contract_version(deps.storage, CONTRACT_NAME, CONTRACT_VERSION)?;
+
+    Ok(Response::new().add_attribute("method", "migrate"))
 }
 
 #[cfg_attr(not(feature = "library"), entry_point)]
@@ -161,13 +201,28 @@ pub fn query(deps: Deps, _env: Env, msg: QueryMsg) -> StdResult<Binary> {
     match msg {
         QueryMsg::GetBalance => to_binary(&query_balance(deps)?),
         QueryMsg::GetTokenAddr => to_binary(&query_token_addr(deps)?),
+        QueryMsg::GetAssets => to_binary(&query_assets(deps)?),
+        QueryMsg::Access(msg) => access::query(deps, msg),
     }
 }
 
+// Address of the ask asset, for clients that expect a cw20 token
 pub fn query_token_addr(deps: Deps) -> StdResult<TokenAddrResponse> {
+    match STATE.load(deps.storage)?.ask_asset {
+        AssetInfo::Cw20(token_address) => Ok(TokenAddrResponse { token_address }),
+        ask_asset => Err(StdError::generic_err(format!(
+            "Ask asset {} is not a cw20 token",
+            ask_asset
+        ))),
+    }
+}
+
+pub fn query_assets(deps: Deps) -> StdResult<AssetsResponse> {
     let state = STATE.load(deps.storage)?;
-    Ok(TokenAddrResponse {
-        token_address: state.token_address,
+    Ok(AssetsResponse {
+        offer_asset: state.offer_asset,
+        ask_asset: state.ask_asset,
+        feed: state.feed,
     })
 }
 
@@ -178,11 +233,261 @@ pub fn query_balance(_deps: Deps) -> StdResult<BalanceResponse> {
 
 #[cfg(test)]
 mod tests {
-    use cosmwasm_std::{coin, testing::mock_info};
+    use super::*;
+    use cosmwasm_std::testing::{mock_env, mock_info, MockApi, MockStorage};
+    use cosmwasm_std::{coins, BankMsg, CosmosMsg, OwnedDeps, Timestamp, WasmMsg};
+    use cw20::Cw20ExecuteMsg;
+    use shared::access::{AccessError, AccessQueryMsg, OwnershipResponse};
+    use shared::asset::AssetInfoUnchecked;
+    use shared::mock_querier::{mock_dependencies, TerraMockQuerier};
+    use shared::oracle::PriceResponse;
+
+    use crate::state::LegacyState;
+
+    // An "oracle" pricing at 2, and an "aurm" token with 6 decimals and a
+    // "wbtc" token with 8 decimals of which the contract holds 1000 each
+    fn mock_deps(
+        contract_balance: &[cosmwasm_std::Coin],
+    ) -> OwnedDeps<MockStorage, MockApi, TerraMockQuerier> {
+        let mut deps = mock_dependencies(contract_balance);
+        let contract = mock_env().contract.address;
+        deps.querier.update_oracle_price(
+            "oracle",
+            None,
+            &PriceResponse {
+                price: Decimal::from_ratio(2u128, 1u128),
+                decimals: 6,
+                quote_denom: "uluna".to_string(),
+                confidence: Decimal::zero(),
+                publishers: 1,
+                last_updated_height: 12345,
+                last_updated_time: Timestamp::from_seconds(0),
+            },
+        );
+        for (token, decimals) in [("aurm", 6), ("wbtc", 8)] {
+            deps.querier.update_token_balance(token, &contract, 1000);
+            deps.querier.update_token_decimals(token, decimals);
+        }
+        deps
+    }
+
+    fn setup(
+        offer_asset: AssetInfoUnchecked,
+        ask_asset: AssetInfoUnchecked,
+        contract_balance: &[cosmwasm_std::Coin],
+    ) -> OwnedDeps<MockStorage, MockApi, TerraMockQuerier> {
+        let mut deps = mock_deps(contract_balance);
+        let msg = InstantiateMsg {
+            oracle_address: Addr::unchecked("oracle"),
+            offer_asset,
+            ask_asset,
+            feed: None,
+        };
+        instantiate(deps.as_mut(), mock_env(), mock_info("creator", &[]), msg).unwrap();
+        deps
+    }
 
     #[test]
     fn proper_initialization() {
+        let deps = setup(
+            AssetInfoUnchecked::native("uluna"),
+            AssetInfoUnchecked::cw20("aurm"),
+            &[],
+        );
+        let res: TokenAddrResponse =
+            from_binary(&query(deps.as_ref(), mock_env(), QueryMsg::GetTokenAddr).unwrap())
+                .unwrap();
+        assert_eq!("aurm", res.token_address);
+
+        let mut deps = mock_deps(&[]);
+        let msg = InstantiateMsg {
+            oracle_address: Addr::unchecked("oracle"),
+            offer_asset: AssetInfoUnchecked::native("uluna"),
+            ask_asset: AssetInfoUnchecked::native("uluna"),
+            feed: None,
+        };
+        let err =
+            instantiate(deps.as_mut(), mock_env(), mock_info("creator", &[]), msg).unwrap_err();
+        assert!(matches!(err, ContractError::SameAssets {}));
+    }
+
+    #[test]
+    fn buys_native_for_cw20() {
+        let mut deps = setup(
+            AssetInfoUnchecked::native("uluna"),
+            AssetInfoUnchecked::cw20("aurm"),
+            &[],
+        );
+
+        let info = mock_info("buyer", &coins(101, "uluna"));
+        let res = execute(deps.as_mut(), mock_env(), info, ExecuteMsg::Buy {}).unwrap();
+        assert_eq!(
+            res.messages[0].msg,
+            CosmosMsg::Wasm(WasmMsg::Execute {
+                contract_addr: "aurm".to_string(),
+                msg: to_binary(&Cw20ExecuteMsg::Transfer {
+                    recipient: "buyer".to_string(),
+                    amount: Uint128::new(50),
+                })
+                .unwrap(),
+                funds: vec![],
+            })
+        );
+
+        let info = mock_info("buyer", &coins(101, "uusd"));
+        let err = execute(deps.as_mut(), mock_env(), info, ExecuteMsg::Buy {}).unwrap_err();
+        assert!(matches!(err, ContractError::Asset(_)));
+
+        // 1 uluna would buy half a unit, which is rounded down to nothing
+        let info = mock_info("buyer", &coins(1, "uluna"));
+        let err = execute(deps.as_mut(), mock_env(), info, ExecuteMsg::Buy {}).unwrap_err();
+        assert!(matches!(err, ContractError::OfferTooSmall { .. }));
+
+        // Only 1000 AURM are left to sell
+        let info = mock_info("buyer", &coins(2002, "uluna"));
+        let err = execute(deps.as_mut(), mock_env(), info, ExecuteMsg::Buy {}).unwrap_err();
+        assert!(matches!(err, ContractError::Std(_)));
+    }
+
+    #[test]
+    fn buys_native_with_cw20() {
+        let mut deps = setup(
+            AssetInfoUnchecked::cw20("aurm"),
+            AssetInfoUnchecked::native("uusd"),
+            &coins(500, "uusd"),
+        );
+        let receive = |amount: u128| {
+            ExecuteMsg::Receive(Cw20ReceiveMsg {
+                sender: "buyer".to_string(),
+                amount: Uint128::new(amount),
+                msg: to_binary(&ReceiveMsg::Buy {}).unwrap(),
+            })
+        };
+
+        let info = mock_info("buyer", &coins(100, "uluna"));
+        let err = execute(deps.as_mut(), mock_env(), info, ExecuteMsg::Buy {}).unwrap_err();
+        assert!(matches!(err, ContractError::Asset(_)));
+
+        // Only the token itself may call Receive
+        let info = mock_info("buyer", &[]);
+        let err = execute(deps.as_mut(), mock_env(), info, receive(100)).unwrap_err();
+        assert!(matches!(err, ContractError::Asset(_)));
+
+        let info = mock_info("aurm", &[]);
+        let res = execute(deps.as_mut(), mock_env(), info, receive(100)).unwrap();
+        assert_eq!(
+            res.messages[0].msg,
+            CosmosMsg::Bank(BankMsg::Send {
+                to_address: "buyer".to_string(),
+                amount: coins(50, "uusd"),
+            })
+        );
+
+        // Withdrawals pay out the collected offer asset, to the owner only
+        let info = mock_info("buyer", &[]);
+        let msg = ExecuteMsg::Withdraw { amount: 100 };
+        let err = execute(deps.as_mut(), mock_env(), info, msg).unwrap_err();
+        assert!(matches!(
+            err,
+            ContractError::Access(AccessError::Unauthorized {})
+        ));
+
         let info = mock_info("creator", &[]);
-        let swapper = mock_info("swapper", &vec![coin(10000000000, "uluna")]);
+        let msg = ExecuteMsg::Withdraw { amount: 100 };
+        let res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+        assert_eq!(
+            res.messages[0].msg,
+            CosmosMsg::Wasm(WasmMsg::Execute {
+                contract_addr: "aurm".to_string(),
+                msg: to_binary(&Cw20ExecuteMsg::Transfer {
+                    recipient: "creator".to_string(),
+                    amount: Uint128::new(100),
+                })
+                .unwrap(),
+                funds: vec![],
+            })
+        );
+
+        let err = query(deps.as_ref(), mock_env(), QueryMsg::GetTokenAddr).unwrap_err();
+        assert_eq!(
+            err,
+            StdError::generic_err("Ask asset native:uusd is not a cw20 token")
+        );
+    }
+
+    #[test]
+    fn buys_scale_by_the_decimals_of_both_assets() {
+        // 2 uluna buy 0.000001 WBTC, which is 100 of its base units
+        let mut deps = setup(
+            AssetInfoUnchecked::native("uluna"),
+            AssetInfoUnchecked::cw20("wbtc"),
+            &[],
+        );
+        let info = mock_info("buyer", &coins(2, "uluna"));
+        let res = execute(deps.as_mut(), mock_env(), info, ExecuteMsg::Buy {}).unwrap();
+        assert_eq!(
+            res.messages[0].msg,
+            CosmosMsg::Wasm(WasmMsg::Execute {
+                contract_addr: "wbtc".to_string(),
+                msg: to_binary(&Cw20ExecuteMsg::Transfer {
+                    recipient: "buyer".to_string(),
+                    amount: Uint128::new(100),
+                })
+                .unwrap(),
+                funds: vec![],
+            })
+        );
+
+        // And 0.000101 WBTC buy 50 uusd, rounded down from 50.5
+        let mut deps = setup(
+            AssetInfoUnchecked::cw20("wbtc"),
+            AssetInfoUnchecked::native("uusd"),
+            &coins(500, "uusd"),
+        );
+        let receive = ExecuteMsg::Receive(Cw20ReceiveMsg {
+            sender: "buyer".to_string(),
+            amount: Uint128::new(10100),
+            msg: to_binary(&ReceiveMsg::Buy {}).unwrap(),
+        });
+        let res = execute(deps.as_mut(), mock_env(), mock_info("wbtc", &[]), receive).unwrap();
+        assert_eq!(
+            res.messages[0].msg,
+            CosmosMsg::Bank(BankMsg::Send {
+                to_address: "buyer".to_string(),
+                amount: coins(50, "uusd"),
+            })
+        );
+    }
+
+    #[test]
+    fn migrate_moves_the_owner_into_access() {
+        let mut deps = mock_deps(&[]);
+        let legacy = LegacyState {
+            owner: Addr::unchecked("creator"),
+            oracle_address: Addr::unchecked("oracle"),
+            token_address: Addr::unchecked("aurm"),
+        };
+        LEGACY_STATE.save(deps.as_mut().storage, &legacy).unwrap();
+
+        let _res = migrate(deps.as_mut(), mock_env(), Empty {}).unwrap();
+        let res: OwnershipResponse = from_binary(
+            &query(
+                deps.as_ref(),
+                mock_env(),
+                QueryMsg::Access(AccessQueryMsg::Ownership {}),
+            )
+            .unwrap(),
+        )
+        .unwrap();
+        assert_eq!(Some(Addr::unchecked("creator")), res.owner);
+
+        // The contract keeps selling its token for uluna
+        let res: AssetsResponse =
+            from_binary(&query(deps.as_ref(), mock_env(), QueryMsg::GetAssets).unwrap()).unwrap();
+        assert_eq!(AssetInfo::Native("uluna".to_string()), res.offer_asset);
+        assert_eq!(AssetInfo::Cw20(Addr::unchecked("aurm")), res.ask_asset);
+        let info = mock_info("buyer", &coins(101, "uluna"));
+        let res = execute(deps.as_mut(), mock_env(), info, ExecuteMsg::Buy {}).unwrap();
+        assert_eq!(1, res.messages.len());
     }
 }
@@ -1,4 +1,7 @@
 use cosmwasm_std::StdError;
+use shared::access::AccessError;
+use shared::asset::AssetError;
+use shared::token::TokenQueryError;
 use thiserror::Error;
 
 #[derive(Error, Debug)]
@@ -6,13 +9,25 @@ pub enum ContractError {
     #[error("{0}")]
     Std(#[from] StdError),
 
-    #[error("Unauthorized")]
-    Unauthorized {},
+    #[error("{0}")]
+    TokenQuery(#[from] TokenQueryError),
+
+    #[error("{0}")]
+    Asset(#[from] AssetError),
+
+    #[error("{0}")]
+    Access(#[from] AccessError),
     // Add any other custom errors you like here.
     // Look at https://docs.rs/thiserror/1.0.21/thiserror/ for details.
     #[error("quantity is invalid")]
     InvalidQuantity,
 
+    #[error("Offer and ask asset must differ")]
+    SameAssets {},
+
+    #[error("{offer} buys less than one unit of {ask_asset}")]
+    OfferTooSmall { offer: String, ask_asset: String },
+
     #[error("Not implemented")]
     NotImplemented {},
 }
@@ -1,39 +1,21 @@
-use cosmwasm_std::Addr;
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
+use shared::asset::AssetInfo;
+use shared::oracle::FeedId;
 
-#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
-#[serde(rename_all = "snake_case")]
-pub struct InstantiateMsg {
-    pub oracle_address: Addr,
-    pub token_address: Addr,
-}
-
-#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
-#[serde(rename_all = "snake_case")]
-pub enum ExecuteMsg {
-    Buy {},
-    Withdraw { amount: i32 },
-}
-
-#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
-#[serde(rename_all = "snake_case")]
-pub enum QueryMsg {
-    /// Returns Token Address
-    GetTokenAddr,
-
-    /// Returns balance of native token
-    GetBalance,
-}
+// The messages are defined in shared, next to the client sending them
+pub use shared::swap::{ExecuteMsg, InstantiateMsg, QueryMsg, ReceiveMsg, TokenAddrResponse};
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 #[serde(rename_all = "snake_case")]
-pub struct TokenAddrResponse {
-    pub token_address: Addr,
+pub struct BalanceResponse {
+    pub balance: i32,
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 #[serde(rename_all = "snake_case")]
-pub struct BalanceResponse {
-    pub balance: i32,
+pub struct AssetsResponse {
+    pub offer_asset: AssetInfo,
+    pub ask_asset: AssetInfo,
+    pub feed: Option<FeedId>,
 }
@@ -0,0 +1,80 @@
+use cosmwasm_std::{coins, Decimal, Uint128};
+use cw_multi_test::ContractWrapper;
+use shared::testing::{Codes, Suite, SuiteBuilder, OWNER};
+
+fn codes() -> Codes {
+    Codes {
+        oracle: Box::new(ContractWrapper::new(
+            oracle::contract::execute,
+            oracle::contract::instantiate,
+            oracle::contract::query,
+        )),
+        token: Box::new(ContractWrapper::new(
+            cw20_token::contract::execute,
+            cw20_token::contract::instantiate,
+            cw20_token::contract::query,
+        )),
+        swap: Box::new(ContractWrapper::new(
+            swap::contract::execute,
+            swap::contract::instantiate,
+            swap::contract::query,
+        )),
+    }
+}
+
+fn suite(price: u64, inventory: u128) -> Suite {
+    SuiteBuilder::new()
+        .with_price(Decimal::from_ratio(price, 1u64))
+        .with_inventory(inventory)
+        .with_funds("buyer", &coins(1000, "uluna"))
+        .build(codes())
+}
+
+#[test]
+fn buy_and_withdraw_lifecycle() {
+    let mut suite = suite(2, 1000);
+    let swap = suite.swap.addr();
+    assert_eq!(
+        suite.token.addr(),
+        suite.swap.query_token_address(&suite.app.wrap()).unwrap()
+    );
+
+    suite.buy("buyer", &coins(200, "uluna")).unwrap();
+    assert_eq!(Uint128::new(100), suite.token_balance("buyer"));
+    assert_eq!(Uint128::new(900), suite.token_balance(&swap));
+    assert_eq!(Uint128::new(800), suite.native_balance("buyer"));
+    assert_eq!(Uint128::new(200), suite.native_balance(&swap));
+
+    // Later buys go through at the new oracle price
+    suite.set_price(Decimal::from_ratio(4u64, 1u64)).unwrap();
+    suite.buy("buyer", &coins(400, "uluna")).unwrap();
+    assert_eq!(Uint128::new(200), suite.token_balance("buyer"));
+    assert_eq!(Uint128::new(600), suite.native_balance(&swap));
+
+    // Only the owner withdraws the collected uluna, and no more than there is
+    suite.withdraw("buyer", 100).unwrap_err();
+    suite.withdraw(OWNER, 601).unwrap_err();
+    suite.withdraw(OWNER, 500).unwrap();
+    assert_eq!(Uint128::new(500), suite.native_balance(OWNER));
+    assert_eq!(Uint128::new(100), suite.native_balance(&swap));
+}
+
+#[test]
+fn failed_buys_leave_no_trace() {
+    let mut suite = suite(1, 10);
+    let swap = suite.swap.addr();
+
+    // More than the inventory, the uluna stays with the buyer
+    suite.buy("buyer", &coins(11, "uluna")).unwrap_err();
+    assert_eq!(Uint128::new(1000), suite.native_balance("buyer"));
+    assert_eq!(Uint128::zero(), suite.native_balance(&swap));
+    assert_eq!(Uint128::new(10), suite.token_balance(&swap));
+
+    suite.buy("buyer", &[]).unwrap_err();
+
+    // Restocking makes the same buy go through
+    suite.stock_inventory(1).unwrap();
+    suite.buy("buyer", &coins(11, "uluna")).unwrap();
+    assert_eq!(Uint128::new(11), suite.token_balance("buyer"));
+    assert_eq!(Uint128::zero(), suite.token_balance(&swap));
+}
@@ -3,7 +3,7 @@ use std::fs::create_dir_all;
 
 use cosmwasm_schema::{export_schema, remove_schemas, schema_for};
 
-use swap2::msg::{ExecuteMsg, InstantiateMsg, QueryMsg};
+use swap2::msg::{ExecuteMsg, InstantiateMsg, QueryMsg, ReceiveMsg};
 
 fn main() {
     let mut out_dir = current_dir().unwrap();
@@ -13,5 +13,6 @@ fn main() {
 
     export_schema(&schema_for!(InstantiateMsg), &out_dir);
     export_schema(&schema_for!(ExecuteMsg), &out_dir);
+    export_schema(&schema_for!(ReceiveMsg), &out_dir);
     export_schema(&schema_for!(QueryMsg), &out_dir);
 }
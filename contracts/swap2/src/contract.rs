@@ -1,22 +1,25 @@
 #[cfg(not(feature = "library"))]
 use cosmwasm_std::entry_point;
 use cosmwasm_std::{
-    coin, to_binary, wasm_execute, BankMsg, Binary, CosmosMsg, Deps, DepsMut, DistributionMsg,
-    Empty, Env, FullDelegation, MessageInfo, Response, StakingMsg, StdError, StdResult, SubMsg,
-    Uint128, WasmMsg,
+    coin, from_binary, to_binary, Addr, BankMsg, Binary, CosmosMsg, Decimal, Deps, DepsMut,
+    DistributionMsg, Empty, Env, FullDelegation, MessageInfo, Response, StakingMsg, StdError,
+    StdResult, SubMsg, Uint128, WasmMsg,
 };
-use cw0::must_pay;
 use cw2::set_contract_version;
-//use cw20::Cw20ExecuteMsg;
-
-use cw20::{Cw20ExecuteMsg, Cw20QueryMsg};
+use cw20::Cw20ReceiveMsg;
 use terra_cosmwasm::{create_swap_msg, ExchangeRatesResponse, TerraMsgWrapper, TerraQuerier};
 
 use crate::error::ContractError;
-use crate::msg::{ExecuteMsg, InstantiateMsg, QueryMsg};
-use crate::state::{State, STATE};
+use crate::msg::{ExecuteMsg, InstantiateMsg, QueryMsg, ReceiveMsg};
+use crate::state::{State, LEGACY_STATE, STATE};
 
-use oracle::msg::{PriceResponse as OraclePriceResponse, QueryMsg::QueryPrice as OracleQueryPrice};
+use shared::access;
+use shared::asset::{
+    must_pay_native, must_receive_cw20, query_balance, query_decimals, transfer_msg, Asset,
+    AssetInfo,
+};
+use shared::math::{div_price, Rounding};
+use shared::oracle::OracleContract;
 
 // version info for migration info
 const CONTRACT_NAME: &str = "crates.io:swap2";
@@ -37,27 +40,57 @@ pub fn instantiate(
     msg: InstantiateMsg,
 ) -> Result<Response, ContractError> {
     set_contract_version(deps.storage, CONTRACT_NAME, CONTRACT_VERSION)?;
+    let offer_asset = msg.offer_asset.check(deps.api)?;
+    let ask_asset = msg.ask_asset.check(deps.api)?;
+    if offer_asset == ask_asset {
+        return Err(ContractError::SameAssets {});
+    }
     STATE.save(
         deps.storage,
         &State {
             oracle_address: msg.oracle_address.clone(),
-            token_address: msg.oracle_address.clone(),
-            owner: info.sender.clone(),
+            offer_decimals: query_decimals(&deps.querier, &offer_asset)?,
+            ask_decimals: query_decimals(&deps.querier, &ask_asset)?,
+            offer_asset,
+            ask_asset,
+            feed: msg.feed,
         },
     )?;
+    access::initialize_owner(deps.storage, &info.sender)?;
 
     Ok(Response::new())
 }
 
 #[cfg_attr(not(feature = "library"), entry_point)]
-pub fn query(_deps: Deps, _env: Env, _msg: QueryMsg) -> StdResult<Binary> {
-    // TODO
-    Err(StdError::generic_err("not implemented"))
+pub fn query(deps: Deps, _env: Env, msg: QueryMsg) -> StdResult<Binary> {
+    match msg {
+        // TODO
+        QueryMsg::QueryTokenAddress {} => Err(StdError::generic_err("not implemented")),
+        QueryMsg::Access(msg) => access::query(deps, msg),
+    }
 }
 
 #[cfg_attr(not(feature = "library"), entry_point)]
-pub fn migrate(_deps: DepsMut, _env: Env, _msg: Empty) -> Result<Response, ContractError> {
-    Ok(Response::default())
+pub fn migrate(deps: DepsMut, _env: Env, _msg: Empty) -> Result<Response, ContractError> {
+    // Contracts instantiated before shared::access kept their owner in STATE
+    if access::OWNER.may_load(deps.storage)?.is_none() {
+        let legacy = LEGACY_STATE.load(deps.storage)?;
+        let offer_asset = AssetInfo::Native("uluna".to_string());
+        let ask_asset = AssetInfo::Cw20(legacy.token_address);
+        let state = State {
+            oracle_address: legacy.oracle_address,
+            offer_decimals: query_decimals(&deps.querier, &offer_asset)?,
+            ask_decimals: query_decimals(&deps.querier, &ask_asset)?,
+            offer_asset,
+            ask_asset,
+            feed: None,
+        };
+        STATE.save(deps.storage, &state)?;
+        access::initialize_owner(deps.storage, &legacy.owner)?;
+    }
+    set_contract_version(deps.storage, CONTRACT_NAME, CONTRACT_VERSION)?;
+
+    Ok(Response::new().add_attribute("method", "migrate"))
 }
 
 #[cfg_attr(not(feature = "library"), entry_point)]
@@ -68,7 +101,12 @@ pub fn execute(
     msg: ExecuteMsg,
 ) -> Result<Response<TerraMsgWrapper>, ContractError> {
     match msg {
-        ExecuteMsg::Buy {} => try_buy(deps, env, info),
+        ExecuteMsg::Buy {} => {
+            let state = STATE.load(deps.storage)?;
+            let offer = must_pay_native(&info, &state.offer_asset)?;
+            try_buy(deps, env, info.sender, offer)
+        }
+        ExecuteMsg::Receive(receive) => try_receive(deps, env, info, receive),
         ExecuteMsg::Withdraw { amount } => {
             try_withdraw_step1_collect_rewards(deps, env, info, amount)
         }
@@ -79,54 +117,80 @@ pub fn execute(
         ExecuteMsg::WithdrawStep3SendLuna { amount } => {
             try_withdraw_step3_send_luna(deps, env, info, amount)
         }
+        ExecuteMsg::Access(msg) => Ok(access::execute(deps, info, msg)?),
     }
 }
 
-pub fn try_buy(
+pub fn try_receive(
     deps: DepsMut,
     env: Env,
     info: MessageInfo,
+    receive: Cw20ReceiveMsg,
 ) -> Result<Response<TerraMsgWrapper>, ContractError> {
-    // Check payment_amt is in uluna, only 1 coin has been supplied, and it's non-zero
-    let payment_amt =
-        must_pay(&info, "uluna").map_err(|error| StdError::generic_err(format!("{}", error)))?;
-
-    // Get AURM price in uluna from oracle
-    let oracle_price = query_aurm_oracle(deps.as_ref())? as u128;
+    let state = STATE.load(deps.storage)?;
+    let offer = must_receive_cw20(&info, &receive, &state.offer_asset)?;
+    let buyer = deps.api.addr_validate(&receive.sender)?;
+    match from_binary(&receive.msg)? {
+        ReceiveMsg::Buy {} => try_buy(deps, env, buyer, offer),
+    }
+}
 
-    // Compute number of AURM tokens user should get at `oracle_price`
-    let swap_aurum_qty = payment_amt.u128() / oracle_price;
+pub fn try_buy(
+    deps: DepsMut,
+    env: Env,
+    buyer: Addr,
+    offer: Asset,
+) -> Result<Response<TerraMsgWrapper>, ContractError> {
+    // Get the ask asset's price in the offer asset from oracle
+    let oracle_price = query_oracle(deps.as_ref())?;
+    if oracle_price.is_zero() {
+        return Err(ContractError::BuyError {});
+    }
 
-    // Get swap2's own AURM balance
-    let self_aurum_balance = query_own_aurm_balance(deps.as_ref(), env)?;
+    // Compute how much of the ask asset the buyer should get at `oracle_price`
+    // Rounded down in favour of the contract
+    let state = STATE.load(deps.storage)?;
+    let ask_amount = div_price(
+        offer.amount,
+        state.offer_decimals,
+        oracle_price,
+        state.ask_decimals,
+        Rounding::Floor,
+    )?;
+    if ask_amount.is_zero() {
+        return Err(ContractError::OfferTooSmall {
+            offer: offer.to_string(),
+            ask_asset: state.ask_asset.to_string(),
+        });
+    }
 
-    // Return a BuyError if contract does not have sufficient AURM to complete txn
-    if self_aurum_balance.u128() < swap_aurum_qty {
+    // Return a BuyError if contract does not have sufficient funds to complete txn
+    let self_ask_balance = query_balance(&deps.querier, &state.ask_asset, &env.contract.address)?;
+    if self_ask_balance < ask_amount {
         return Err(ContractError::BuyError {});
     }
 
-    let state = STATE.load(deps.storage)?;
+    let mut msgs = vec![];
 
-    // Make message for transferring AURM to user
-    let transfer_aurm_to_user_msg = wasm_execute(
-        state.token_address,
-        &Cw20ExecuteMsg::Transfer {
-            recipient: info.sender.to_string(),
-            amount: Uint128::from(swap_aurum_qty),
-        },
-        vec![],
-    )?;
+    // Delegate the offer to a validator immediately if it can be staked
+    if let AssetInfo::Native(denom) = &offer.info {
+        if *denom == deps.querier.query_bonded_denom()? {
+            msgs.push(CosmosMsg::Staking(StakingMsg::Delegate {
+                validator: VALIDATOR.to_string(),
+                amount: coin(offer.amount.u128(), denom),
+            }));
+        }
+    }
 
-    let msgs = vec![
-        // Delegate new luna generated to a validator immediately
-        CosmosMsg::Staking(StakingMsg::Delegate {
-            validator: VALIDATOR.to_string(),
-            amount: coin(payment_amt.u128(), String::from("uluna")),
-        }),
-        transfer_aurm_to_user_msg.into(),
-    ];
+    // Make message for transferring the ask asset to user
+    let ask = Asset::new(state.ask_asset, ask_amount);
+    msgs.push(transfer_msg(&ask, &buyer)?);
 
-    Ok(Response::<TerraMsgWrapper>::new().add_messages(msgs))
+    Ok(Response::<TerraMsgWrapper>::new()
+        .add_attribute("method", "try_buy")
+        .add_attribute("offer", offer.to_string())
+        .add_attribute("ask", ask.to_string())
+        .add_messages(msgs))
 }
 
 pub fn try_withdraw_step1_collect_rewards(
@@ -156,9 +220,17 @@ pub fn try_withdraw_step1_collect_rewards(
 }
 
 pub fn collect_all_rewards(
-    _deps: DepsMut,
-    _env: &Env,
+    deps: DepsMut,
+    env: &Env,
 ) -> Result<Vec<SubMsg<TerraMsgWrapper>>, ContractError> {
+    // Nothing is delegated when the offer asset can't be staked
+    let delegation = deps
+        .querier
+        .query_delegation(env.contract.address.clone(), VALIDATOR.to_string())?;
+    if delegation.is_none() {
+        return Ok(vec![]);
+    }
+
     let withdraw_rewards_msg: SubMsg<TerraMsgWrapper> = SubMsg::new(CosmosMsg::Distribution(
         DistributionMsg::WithdrawDelegatorReward {
             validator: VALIDATOR.to_string(),
@@ -176,11 +248,21 @@ pub fn try_withdraw_step2_convert_all_native_coins_to_luna(
 ) -> Result<Response<TerraMsgWrapper>, ContractError> {
     let mut msgs: Vec<SubMsg<TerraMsgWrapper>> = Vec::new();
 
+    let state = STATE.load(deps.storage)?;
     let all_bals = deps
         .querier
         .query_all_balances(env.contract.address.to_string())?;
 
-    for bal in all_bals {
+    // The market module rejects swaps into the offered denom, and the traded
+    // assets are kept as they are: the ask asset is stock, the offer asset is
+    // paid out in step 3
+    let kept = |denom: &str| {
+        denom == "uluna"
+            || [&state.offer_asset, &state.ask_asset]
+                .iter()
+                .any(|asset| matches!(asset, AssetInfo::Native(d) if d == denom))
+    };
+    for bal in all_bals.into_iter().filter(|bal| !kept(&bal.denom)) {
         msgs.push(SubMsg::new(create_swap_msg(
             bal.clone(),
             "uluna".to_string(),
@@ -208,19 +290,38 @@ pub fn try_withdraw_step3_send_luna(
     _info: MessageInfo,
     amount: u64,
 ) -> Result<Response<TerraMsgWrapper>, ContractError> {
-    let bal = deps.querier.query_balance(env.contract.address, "uluna")?;
+    let bal = deps
+        .querier
+        .query_balance(env.contract.address.clone(), "uluna")?;
 
-    if bal.amount.u128() <= (amount as u128) {
+    if amount > 0 && bal.amount.u128() <= (amount as u128) {
         return Err(ContractError::InvalidQuantity);
     }
 
-    let state = STATE.load(deps.storage)?;
-    let mut msgs: Vec<SubMsg<TerraMsgWrapper>> = Vec::new();
+    let owner = access::OWNER
+        .load(deps.storage)?
+        .ok_or_else(|| StdError::generic_err("Ownership has been renounced"))?;
+    let mut msgs: Vec<SubMsg<TerraMsgWrapper>> = vec![];
+    if amount > 0 {
+        msgs.push(SubMsg::new(CosmosMsg::Bank(BankMsg::Send {
+            to_address: owner.to_string(),
+            amount: vec![coin(amount as u128, "uluna")],
+        })));
+    }
 
-    msgs.push(SubMsg::new(CosmosMsg::Bank(BankMsg::Send {
-        to_address: state.owner.to_string(),
-        amount: vec![coin(amount as u128, "uluna")],
-    })));
+    // An offer asset that isn't staked is collected as is, send all of it
+    let state = STATE.load(deps.storage)?;
+    let staked = match &state.offer_asset {
+        AssetInfo::Native(denom) => *denom == deps.querier.query_bonded_denom()?,
+        AssetInfo::Cw20(_) => false,
+    };
+    if !staked {
+        let collected = query_balance(&deps.querier, &state.offer_asset, &env.contract.address)?;
+        if !collected.is_zero() {
+            let offer = Asset::new(state.offer_asset, collected);
+            msgs.push(SubMsg::new(transfer_msg(&offer, &owner)?));
+        }
+    }
 
     Ok(Response::new()
         .add_attribute("method", "try_withdraw_step3_send_luna")
@@ -233,10 +334,7 @@ pub fn try_start_undelegation(
     info: MessageInfo,
     amount: Uint128,
 ) -> Result<Response<TerraMsgWrapper>, ContractError> {
-    let state = STATE.load(deps.storage)?;
-    if state.owner != info.sender.to_string() {
-        return Err(ContractError::Unauthorized {});
-    }
+    access::assert_owner(deps.storage, &info.sender)?;
 
     let delegation = deps
         .querier
@@ -258,10 +356,10 @@ pub fn try_start_undelegation(
         }
     }
 
-    return Err(StdError::GenericErr {
+    Err(StdError::GenericErr {
         msg: "No delegation found".to_string(),
     }
-    .into());
+    .into())
 }
 
 pub fn query_exchange_rates(
@@ -274,35 +372,85 @@ pub fn query_exchange_rates(
     Ok(res)
 }
 
-pub fn query_aurm_oracle(deps: Deps) -> Result<u64, ContractError> {
+pub fn query_oracle(deps: Deps) -> Result<Decimal, ContractError> {
     let state = STATE.load(deps.storage)?;
-    let msg = OracleQueryPrice {};
-    let resp: OraclePriceResponse = deps.querier.query_wasm_smart(state.oracle_address, &msg)?;
+    let resp =
+        OracleContract(state.oracle_address).query_price(&deps.querier, state.feed, None, None)?;
     Ok(resp.price)
 }
 
-pub fn query_own_aurm_balance(deps: Deps, env: Env) -> Result<Uint128, ContractError> {
-    let state = STATE.load(deps.storage)?;
-    let msg = Cw20QueryMsg::Balance {
-        address: env.contract.address.to_string(),
-    };
-    let resp: cw20::BalanceResponse = deps.querier.query_wasm_smart(state.token_address, &msg)?;
-    Ok(resp.balance)
-}
-
 #[cfg(test)]
 mod tests {
     use super::*;
-    use cosmwasm_std::testing::{mock_dependencies, mock_env, mock_info};
-    use cosmwasm_std::{coins, Addr};
+    use cosmwasm_std::testing::{mock_env, mock_info};
+    use cosmwasm_std::{coins, Timestamp, Validator};
+    use cw20::Cw20ExecuteMsg;
+    use shared::access::{AccessQueryMsg, OwnershipResponse};
+    use shared::asset::AssetInfoUnchecked;
+    use shared::mock_app::TerraApp;
+    use shared::mock_querier::mock_dependencies;
+    use shared::oracle::PriceResponse;
+
+    use crate::state::LegacyState;
+
+    // swap2 selling `ask_asset` for `offer_asset` at an oracle price of 2,
+    // with 1000 AURM (6 decimals) in stock, WBTC with 8 decimals and
+    // VALIDATOR in the active set
+    fn setup(
+        offer_asset: AssetInfoUnchecked,
+        ask_asset: AssetInfoUnchecked,
+    ) -> TerraApp<ExecuteMsg, ContractError> {
+        let mut app = TerraApp::new(execute);
+        let contract = app.env.contract.address.clone();
+        let querier = app.querier();
+        querier.update_oracle_price(
+            "oracle",
+            None,
+            &PriceResponse {
+                price: Decimal::from_ratio(2u128, 1u128),
+                decimals: 6,
+                quote_denom: "uluna".to_string(),
+                confidence: Decimal::zero(),
+                publishers: 1,
+                last_updated_height: 12345,
+                last_updated_time: Timestamp::from_seconds(0),
+            },
+        );
+        querier.update_token_balance("aurm", contract, 1000);
+        querier.update_token_decimals("aurm", 6);
+        querier.update_token_decimals("wbtc", 8);
+        querier.update_staking(
+            &[Validator {
+                address: VALIDATOR.to_string(),
+                commission: Decimal::zero(),
+                max_commission: Decimal::percent(10),
+                max_change_rate: Decimal::percent(1),
+            }],
+            &[],
+        );
+
+        let msg = InstantiateMsg {
+            oracle_address: Addr::unchecked("oracle"),
+            offer_asset,
+            ask_asset,
+            feed: None,
+        };
+        let env = app.env.clone();
+        instantiate(app.deps.as_mut(), env, mock_info("creator", &[]), msg).unwrap();
+        app
+    }
 
     #[test]
     fn proper_initialization() {
         let mut deps = mock_dependencies(&[]);
+        deps.querier
+            .update_token_decimals("terra1hpajld8zs93md8zrs6sfy42zl0khqpmr07muw0", 6);
 
         let msg = InstantiateMsg {
-            token_address: Addr::unchecked("terra1hpajld8zs93md8zrs6sfy42zl0khqpmr07muw0"),
             oracle_address: Addr::unchecked("oracle_addr"),
+            offer_asset: AssetInfoUnchecked::native("uluna"),
+            ask_asset: AssetInfoUnchecked::cw20("terra1hpajld8zs93md8zrs6sfy42zl0khqpmr07muw0"),
+            feed: None,
         };
         let info = mock_info("creator", &coins(10000000000, "uluna"));
 
@@ -320,4 +468,238 @@ mod tests {
         //     value.token_address
         // );
     }
+
+    #[test]
+    fn buys_delegate_only_the_staking_denom() {
+        let mut app = setup(
+            AssetInfoUnchecked::native("uluna"),
+            AssetInfoUnchecked::cw20("aurm"),
+        );
+        let contract = app.env.contract.address.to_string();
+        app.querier().update_balance("buyer", coins(100, "uluna"));
+
+        let res = app
+            .execute("buyer", &coins(100, "uluna"), ExecuteMsg::Buy {})
+            .unwrap();
+        let delegations = app
+            .deps
+            .as_ref()
+            .querier
+            .query_all_delegations(&contract)
+            .unwrap();
+        assert_eq!(coin(100, "uluna"), delegations[0].amount);
+        assert_eq!(Uint128::zero(), app.balance(&contract, "uluna"));
+        assert_eq!(
+            res.unhandled,
+            vec![CosmosMsg::Wasm(WasmMsg::Execute {
+                contract_addr: "aurm".to_string(),
+                msg: to_binary(&Cw20ExecuteMsg::Transfer {
+                    recipient: "buyer".to_string(),
+                    amount: Uint128::new(50),
+                })
+                .unwrap(),
+                funds: vec![],
+            })]
+        );
+
+        // A cw20 offer is kept as is, the native ask asset goes to the buyer
+        let mut app = setup(
+            AssetInfoUnchecked::cw20("aurm"),
+            AssetInfoUnchecked::native("uusd"),
+        );
+        app.querier()
+            .update_balance(contract.clone(), coins(500, "uusd"));
+
+        let receive = ExecuteMsg::Receive(Cw20ReceiveMsg {
+            sender: "buyer".to_string(),
+            amount: Uint128::new(100),
+            msg: to_binary(&ReceiveMsg::Buy {}).unwrap(),
+        });
+        let res = app.execute("aurm", &[], receive).unwrap();
+        assert!(res.unhandled.is_empty());
+        assert_eq!(Uint128::new(50), app.balance("buyer", "uusd"));
+        assert_eq!(Uint128::new(450), app.balance(&contract, "uusd"));
+    }
+
+    #[test]
+    fn buys_scale_by_the_decimals_of_both_assets() {
+        // 0.000101 WBTC buy 50 uusd, rounded down from 50.5
+        let mut app = setup(
+            AssetInfoUnchecked::cw20("wbtc"),
+            AssetInfoUnchecked::native("uusd"),
+        );
+        let contract = app.env.contract.address.to_string();
+        app.querier()
+            .update_balance(contract.clone(), coins(500, "uusd"));
+
+        let receive = ExecuteMsg::Receive(Cw20ReceiveMsg {
+            sender: "buyer".to_string(),
+            amount: Uint128::new(10100),
+            msg: to_binary(&ReceiveMsg::Buy {}).unwrap(),
+        });
+        app.execute("wbtc", &[], receive).unwrap();
+        assert_eq!(Uint128::new(50), app.balance("buyer", "uusd"));
+        assert_eq!(Uint128::new(450), app.balance(&contract, "uusd"));
+
+        // 199 base units of WBTC are worth less than 1 uusd
+        let receive = ExecuteMsg::Receive(Cw20ReceiveMsg {
+            sender: "buyer".to_string(),
+            amount: Uint128::new(199),
+            msg: to_binary(&ReceiveMsg::Buy {}).unwrap(),
+        });
+        let err = app.execute("wbtc", &[], receive).unwrap_err();
+        assert_eq!(
+            "cw20:wbtc:199 buys less than one unit of native:uusd",
+            err.to_string()
+        );
+    }
+
+    #[test]
+    fn withdraw_converts_rewards_to_luna() {
+        let mut app = setup(
+            AssetInfoUnchecked::native("uluna"),
+            AssetInfoUnchecked::cw20("aurm"),
+        );
+        let contract = app.env.contract.address.to_string();
+        app.querier().update_balance("buyer", coins(1000, "uluna"));
+        app.execute("buyer", &coins(1000, "uluna"), ExecuteMsg::Buy {})
+            .unwrap();
+
+        // Rewards accrue in several denoms, 1 uluna is worth 40 uusd
+        let querier = app.querier();
+        querier.update_exchange_rate("uusd", Decimal::from_ratio(40u128, 1u128));
+        querier.update_exchange_rate("ukrw", Decimal::from_ratio(48000u128, 1u128));
+        let rewards = vec![coin(5, "uluna"), coin(400, "uusd"), coin(96000, "ukrw")];
+        querier.update_rewards(&contract, VALIDATOR, rewards);
+
+        // 5 + 10 + 2 uluna once everything is swapped, so 18 is too much
+        let msg = ExecuteMsg::Withdraw { amount: 18 };
+        app.execute("creator", &[], msg).unwrap_err();
+        assert_eq!(Uint128::zero(), app.balance(&contract, "uluna"));
+
+        let msg = ExecuteMsg::Withdraw { amount: 16 };
+        let res = app.execute("creator", &[], msg).unwrap();
+        assert!(res.unhandled.is_empty());
+        assert_eq!(Uint128::new(16), app.balance("creator", "uluna"));
+        assert_eq!(Uint128::new(1), app.balance(&contract, "uluna"));
+        assert_eq!(Uint128::zero(), app.balance(&contract, "uusd"));
+        assert_eq!(Uint128::zero(), app.balance(&contract, "ukrw"));
+
+        // Only the owner can start undelegating
+        let msg = ExecuteMsg::StartUndelegation {
+            amount: Uint128::new(400),
+        };
+        app.execute("buyer", &[], msg.clone()).unwrap_err();
+        app.execute("creator", &[], msg).unwrap();
+        let delegation = app
+            .deps
+            .as_ref()
+            .querier
+            .query_delegation(&contract, VALIDATOR)
+            .unwrap()
+            .unwrap();
+        assert_eq!(coin(600, "uluna"), delegation.amount);
+    }
+
+    #[test]
+    fn withdraw_keeps_the_native_ask_asset() {
+        let mut app = setup(
+            AssetInfoUnchecked::native("uluna"),
+            AssetInfoUnchecked::native("uusd"),
+        );
+        let contract = app.env.contract.address.to_string();
+        app.querier()
+            .update_balance(contract.clone(), coins(500, "uusd"));
+        app.querier().update_balance("buyer", coins(100, "uluna"));
+        app.execute("buyer", &coins(100, "uluna"), ExecuteMsg::Buy {})
+            .unwrap();
+
+        let querier = app.querier();
+        querier.update_exchange_rate("uusd", Decimal::from_ratio(40u128, 1u128));
+        querier.update_exchange_rate("ukrw", Decimal::from_ratio(48000u128, 1u128));
+        let rewards = vec![coin(5, "uluna"), coin(96000, "ukrw")];
+        querier.update_rewards(&contract, VALIDATOR, rewards);
+
+        // Only the ukrw rewards are swapped, the uusd left is still for sale
+        let msg = ExecuteMsg::Withdraw { amount: 6 };
+        let res = app.execute("creator", &[], msg).unwrap();
+        assert!(res.unhandled.is_empty());
+        assert_eq!(Uint128::new(6), app.balance("creator", "uluna"));
+        assert_eq!(Uint128::new(1), app.balance(&contract, "uluna"));
+        assert_eq!(Uint128::zero(), app.balance(&contract, "ukrw"));
+        assert_eq!(Uint128::new(450), app.balance(&contract, "uusd"));
+    }
+
+    #[test]
+    fn withdraw_pays_out_an_offer_asset_that_is_not_staked() {
+        // A native offer that isn't staked is sent as is, not swapped to uluna
+        let mut app = setup(
+            AssetInfoUnchecked::native("uusd"),
+            AssetInfoUnchecked::cw20("aurm"),
+        );
+        let contract = app.env.contract.address.to_string();
+        app.querier()
+            .update_exchange_rate("uusd", Decimal::from_ratio(40u128, 1u128));
+        app.querier().update_balance("buyer", coins(100, "uusd"));
+        app.execute("buyer", &coins(100, "uusd"), ExecuteMsg::Buy {})
+            .unwrap();
+
+        let msg = ExecuteMsg::Withdraw { amount: 0 };
+        let res = app.execute("creator", &[], msg).unwrap();
+        assert!(res.unhandled.is_empty());
+        assert_eq!(Uint128::new(100), app.balance("creator", "uusd"));
+        assert_eq!(Uint128::zero(), app.balance(&contract, "uusd"));
+        assert_eq!(Uint128::zero(), app.balance("creator", "uluna"));
+
+        // A cw20 offer is sent back through the token
+        let mut app = setup(
+            AssetInfoUnchecked::cw20("aurm"),
+            AssetInfoUnchecked::native("uusd"),
+        );
+        app.querier().update_token_balance("aurm", contract, 100);
+
+        let msg = ExecuteMsg::Withdraw { amount: 0 };
+        let res = app.execute("creator", &[], msg).unwrap();
+        assert_eq!(
+            res.unhandled,
+            vec![CosmosMsg::Wasm(WasmMsg::Execute {
+                contract_addr: "aurm".to_string(),
+                msg: to_binary(&Cw20ExecuteMsg::Transfer {
+                    recipient: "creator".to_string(),
+                    amount: Uint128::new(100),
+                })
+                .unwrap(),
+                funds: vec![],
+            })]
+        );
+    }
+
+    #[test]
+    fn migrate_moves_the_owner_into_access() {
+        let mut deps = mock_dependencies(&[]);
+        deps.querier.update_token_decimals("aurm", 6);
+        let legacy = LegacyState {
+            owner: Addr::unchecked("creator"),
+            oracle_address: Addr::unchecked("oracle"),
+            token_address: Addr::unchecked("aurm"),
+        };
+        LEGACY_STATE.save(deps.as_mut().storage, &legacy).unwrap();
+
+        let _res = migrate(deps.as_mut(), mock_env(), Empty {}).unwrap();
+        let res: OwnershipResponse = from_binary(
+            &query(
+                deps.as_ref(),
+                mock_env(),
+                QueryMsg::Access(AccessQueryMsg::Ownership {}),
+            )
+            .unwrap(),
+        )
+        .unwrap();
+        assert_eq!(Some(Addr::unchecked("creator")), res.owner);
+
+        let state = STATE.load(deps.as_ref().storage).unwrap();
+        assert_eq!(AssetInfo::Native("uluna".to_string()), state.offer_asset);
+        assert_eq!(AssetInfo::Cw20(Addr::unchecked("aurm")), state.ask_asset);
+        assert_eq!((6, 6), (state.offer_decimals, state.ask_decimals));
+    }
 }
@@ -1,19 +1,33 @@
 use cosmwasm_std::{Addr, Uint128};
+use cw20::Cw20ReceiveMsg;
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
+use shared::access::{AccessExecuteMsg, AccessQueryMsg};
+use shared::asset::AssetInfoUnchecked;
+use shared::oracle::FeedId;
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 pub struct InstantiateMsg {
     pub oracle_address: Addr,
-    pub token_address: Addr,
+    // Asset buyers pay with, delegated right away if it's the staking denom
+    pub offer_asset: AssetInfoUnchecked,
+    // Asset buyers get in return
+    pub ask_asset: AssetInfoUnchecked,
+    // Oracle feed pricing the ask asset in the offer asset, the oracle's
+    // default feed if not set
+    #[serde(default)]
+    pub feed: Option<FeedId>,
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 #[serde(rename_all = "snake_case")]
 pub enum ExecuteMsg {
-    // Buy
+    // Buy with a native offer asset
     Buy {},
 
+    // Buy with a cw20 offer asset, sent through the token's Send
+    Receive(Cw20ReceiveMsg),
+
     // Withdraw
     Withdraw { amount: u64 }, // Step 1: claim rewards from validators
     WithdrawStep2ConvertRewardsToLuna { amount: u64 },
@@ -21,12 +35,23 @@ pub enum ExecuteMsg {
 
     // StartUndelegation
     StartUndelegation { amount: Uint128 },
+
+    // Ownership transfer, withdrawals are paid out to the owner
+    Access(AccessExecuteMsg),
+}
+
+// Message embedded in the cw20 Send of the offer asset
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum ReceiveMsg {
+    Buy {},
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 #[serde(rename_all = "snake_case")]
 pub enum QueryMsg {
     QueryTokenAddress {},
+    Access(AccessQueryMsg),
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
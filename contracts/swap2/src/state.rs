@@ -3,12 +3,30 @@ use serde::{Deserialize, Serialize};
 
 use cosmwasm_std::Addr;
 use cw_storage_plus::Item;
+use shared::asset::AssetInfo;
+use shared::oracle::FeedId;
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 pub struct State {
+    pub oracle_address: Addr,
+    pub offer_asset: AssetInfo,
+    pub ask_asset: AssetInfo,
+    // Looked up once at instantiation, as they never change
+    pub offer_decimals: u8,
+    pub ask_decimals: u8,
+    // None for the oracle's default feed
+    pub feed: Option<FeedId>,
+}
+
+pub const STATE: Item<State> = Item::new("state");
+
+// State as stored before the owner moved to shared::access, back when the
+// contract sold `token_address` for uluna. Only read by `migrate`.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct LegacyState {
     pub owner: Addr,
     pub oracle_address: Addr,
     pub token_address: Addr,
 }
 
-pub const STATE: Item<State> = Item::new("state");
+pub const LEGACY_STATE: Item<LegacyState> = Item::new("state");
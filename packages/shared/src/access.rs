@@ -0,0 +1,379 @@
+use cosmwasm_std::{
+    to_binary, Addr, Binary, Deps, DepsMut, Empty, MessageInfo, Order, Response, StdError,
+    StdResult, Storage,
+};
+use cw_storage_plus::{Bound, Item, Map};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+const MAX_LIMIT: u32 = 30;
+const DEFAULT_LIMIT: u32 = 10;
+
+/// None once ownership has been renounced. Contracts using this module keep
+/// no owner in their own state.
+pub const OWNER: Item<Option<Addr>> = Item::new("access_owner");
+/// Proposed new owner, until they accept
+pub const PENDING_OWNER: Item<Option<Addr>> = Item::new("access_pending_owner");
+/// Members of each named role
+pub const ROLES: Map<(&str, &Addr), Empty> = Map::new("access_roles");
+
+#[derive(Error, Debug, PartialEq)]
+pub enum AccessError {
+    #[error("{0}")]
+    Std(#[from] StdError),
+
+    #[error("Unauthorized")]
+    Unauthorized {},
+
+    #[error("No ownership transfer is pending")]
+    NoPendingOwner {},
+}
+
+/// Ownership messages a contract embeds in its ExecuteMsg as
+/// `Access(AccessExecuteMsg)` and hands to `execute`
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum AccessExecuteMsg {
+    /// Starts handing ownership over, `new_owner` has to accept it
+    TransferOwnership {
+        new_owner: String,
+    },
+    AcceptOwnership {},
+    /// Leaves the contract without an owner for good
+    RenounceOwnership {},
+    GrantRole {
+        role: String,
+        address: String,
+    },
+    RevokeRole {
+        role: String,
+        address: String,
+    },
+}
+
+/// Queries a contract embeds in its QueryMsg as `Access(AccessQueryMsg)` and
+/// hands to `query`
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum AccessQueryMsg {
+    Ownership {},
+    /// Members of `role` ordered by address, the owner is not listed
+    RoleMembers {
+        role: String,
+        start_after: Option<String>,
+        limit: Option<u32>,
+    },
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct OwnershipResponse {
+    pub owner: Option<Addr>,
+    pub pending_owner: Option<Addr>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct RoleMembersResponse {
+    pub members: Vec<Addr>,
+}
+
+/// Sets the first owner, usually from `instantiate`
+pub fn initialize_owner(storage: &mut dyn Storage, owner: &Addr) -> StdResult<()> {
+    OWNER.save(storage, &Some(owner.clone()))?;
+    PENDING_OWNER.save(storage, &None)
+}
+
+pub fn is_owner(storage: &dyn Storage, address: &Addr) -> StdResult<bool> {
+    Ok(OWNER.load(storage)?.as_ref() == Some(address))
+}
+
+/// The owner holds every role without being granted it
+pub fn has_role(storage: &dyn Storage, role: &str, address: &Addr) -> StdResult<bool> {
+    Ok(is_owner(storage, address)? || ROLES.has(storage, (role, address)))
+}
+
+pub fn assert_owner(storage: &dyn Storage, sender: &Addr) -> Result<(), AccessError> {
+    if !is_owner(storage, sender)? {
+        return Err(AccessError::Unauthorized {});
+    }
+    Ok(())
+}
+
+pub fn assert_role(storage: &dyn Storage, role: &str, sender: &Addr) -> Result<(), AccessError> {
+    if !has_role(storage, role, sender)? {
+        return Err(AccessError::Unauthorized {});
+    }
+    Ok(())
+}
+
+pub fn execute<C>(
+    deps: DepsMut,
+    info: MessageInfo,
+    msg: AccessExecuteMsg,
+) -> Result<Response<C>, AccessError>
+where
+    C: Clone + std::fmt::Debug + PartialEq + JsonSchema,
+{
+    match msg {
+        AccessExecuteMsg::TransferOwnership { new_owner } => {
+            try_transfer_ownership(deps, info, new_owner)
+        }
+        AccessExecuteMsg::AcceptOwnership {} => try_accept_ownership(deps, info),
+        AccessExecuteMsg::RenounceOwnership {} => try_renounce_ownership(deps, info),
+        AccessExecuteMsg::GrantRole { role, address } => try_grant_role(deps, info, role, address),
+        AccessExecuteMsg::RevokeRole { role, address } => {
+            try_revoke_role(deps, info, role, address)
+        }
+    }
+}
+
+pub fn query(deps: Deps, msg: AccessQueryMsg) -> StdResult<Binary> {
+    match msg {
+        AccessQueryMsg::Ownership {} => to_binary(&query_ownership(deps)?),
+        AccessQueryMsg::RoleMembers {
+            role,
+            start_after,
+            limit,
+        } => to_binary(&query_role_members(deps, role, start_after, limit)?),
+    }
+}
+
+fn try_transfer_ownership<C>(
+    deps: DepsMut,
+    info: MessageInfo,
+    new_owner: String,
+) -> Result<Response<C>, AccessError>
+where
+    C: Clone + std::fmt::Debug + PartialEq + JsonSchema,
+{
+    assert_owner(deps.storage, &info.sender)?;
+    let new_owner = deps.api.addr_validate(&new_owner)?;
+    PENDING_OWNER.save(deps.storage, &Some(new_owner.clone()))?;
+
+    Ok(Response::new()
+        .add_attribute("method", "try_transfer_ownership")
+        .add_attribute("owner", info.sender)
+        .add_attribute("pending_owner", new_owner))
+}
+
+fn try_accept_ownership<C>(deps: DepsMut, info: MessageInfo) -> Result<Response<C>, AccessError>
+where
+    C: Clone + std::fmt::Debug + PartialEq + JsonSchema,
+{
+    let pending_owner = PENDING_OWNER
+        .load(deps.storage)?
+        .ok_or(AccessError::NoPendingOwner {})?;
+    if info.sender != pending_owner {
+        return Err(AccessError::Unauthorized {});
+    }
+
+    let previous_owner = OWNER.load(deps.storage)?;
+    OWNER.save(deps.storage, &Some(pending_owner))?;
+    PENDING_OWNER.save(deps.storage, &None)?;
+
+    Ok(Response::new()
+        .add_attribute("method", "try_accept_ownership")
+        .add_attribute(
+            "previous_owner",
+            previous_owner.map_or_else(|| "none".to_string(), String::from),
+        )
+        .add_attribute("owner", info.sender))
+}
+
+fn try_renounce_ownership<C>(deps: DepsMut, info: MessageInfo) -> Result<Response<C>, AccessError>
+where
+    C: Clone + std::fmt::Debug + PartialEq + JsonSchema,
+{
+    assert_owner(deps.storage, &info.sender)?;
+    OWNER.save(deps.storage, &None)?;
+    PENDING_OWNER.save(deps.storage, &None)?;
+
+    Ok(Response::new()
+        .add_attribute("method", "try_renounce_ownership")
+        .add_attribute("previous_owner", info.sender))
+}
+
+fn try_grant_role<C>(
+    deps: DepsMut,
+    info: MessageInfo,
+    role: String,
+    address: String,
+) -> Result<Response<C>, AccessError>
+where
+    C: Clone + std::fmt::Debug + PartialEq + JsonSchema,
+{
+    assert_owner(deps.storage, &info.sender)?;
+    let address = deps.api.addr_validate(&address)?;
+    ROLES.save(deps.storage, (&role, &address), &Empty {})?;
+
+    Ok(Response::new()
+        .add_attribute("method", "try_grant_role")
+        .add_attribute("role", role)
+        .add_attribute("address", address))
+}
+
+fn try_revoke_role<C>(
+    deps: DepsMut,
+    info: MessageInfo,
+    role: String,
+    address: String,
+) -> Result<Response<C>, AccessError>
+where
+    C: Clone + std::fmt::Debug + PartialEq + JsonSchema,
+{
+    assert_owner(deps.storage, &info.sender)?;
+    let address = deps.api.addr_validate(&address)?;
+    ROLES.remove(deps.storage, (&role, &address));
+
+    Ok(Response::new()
+        .add_attribute("method", "try_revoke_role")
+        .add_attribute("role", role)
+        .add_attribute("address", address))
+}
+
+pub fn query_ownership(deps: Deps) -> StdResult<OwnershipResponse> {
+    Ok(OwnershipResponse {
+        owner: OWNER.load(deps.storage)?,
+        pending_owner: PENDING_OWNER.load(deps.storage)?,
+    })
+}
+
+pub fn query_role_members(
+    deps: Deps,
+    role: String,
+    start_after: Option<String>,
+    limit: Option<u32>,
+) -> StdResult<RoleMembersResponse> {
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+    let start = start_after.map(|addr| Bound::exclusive(addr.as_str()));
+
+    let members = ROLES
+        .prefix(&role)
+        .keys(deps.storage, start, None, Order::Ascending)
+        .take(limit)
+        .map(|member| Ok(Addr::unchecked(String::from_utf8(member)?)))
+        .collect::<StdResult<Vec<_>>>()?;
+
+    Ok(RoleMembersResponse { members })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use cosmwasm_std::from_binary;
+    use cosmwasm_std::testing::{mock_dependencies, mock_info};
+
+    fn run(
+        deps: DepsMut,
+        sender: &str,
+        msg: AccessExecuteMsg,
+    ) -> Result<Response<Empty>, AccessError> {
+        execute(deps, mock_info(sender, &[]), msg)
+    }
+
+    fn ownership(deps: Deps) -> OwnershipResponse {
+        from_binary(&query(deps, AccessQueryMsg::Ownership {}).unwrap()).unwrap()
+    }
+
+    #[test]
+    fn ownership_is_handed_over_in_two_steps() {
+        let mut deps = mock_dependencies(&[]);
+        initialize_owner(&mut deps.storage, &Addr::unchecked("owner")).unwrap();
+
+        let err = run(deps.as_mut(), "owner", AccessExecuteMsg::AcceptOwnership {}).unwrap_err();
+        assert_eq!(err, AccessError::NoPendingOwner {});
+
+        let transfer = AccessExecuteMsg::TransferOwnership {
+            new_owner: "new_owner".to_string(),
+        };
+        let err = run(deps.as_mut(), "new_owner", transfer.clone()).unwrap_err();
+        assert_eq!(err, AccessError::Unauthorized {});
+        run(deps.as_mut(), "owner", transfer).unwrap();
+
+        // The old owner keeps control until the transfer is accepted
+        assert_eq!(
+            ownership(deps.as_ref()),
+            OwnershipResponse {
+                owner: Some(Addr::unchecked("owner")),
+                pending_owner: Some(Addr::unchecked("new_owner")),
+            }
+        );
+        let err = run(deps.as_mut(), "owner", AccessExecuteMsg::AcceptOwnership {}).unwrap_err();
+        assert_eq!(err, AccessError::Unauthorized {});
+
+        run(
+            deps.as_mut(),
+            "new_owner",
+            AccessExecuteMsg::AcceptOwnership {},
+        )
+        .unwrap();
+        assert_eq!(
+            ownership(deps.as_ref()),
+            OwnershipResponse {
+                owner: Some(Addr::unchecked("new_owner")),
+                pending_owner: None,
+            }
+        );
+        assert_owner(&deps.storage, &Addr::unchecked("owner")).unwrap_err();
+
+        // Renouncing drops a pending transfer too
+        let transfer = AccessExecuteMsg::TransferOwnership {
+            new_owner: "owner".to_string(),
+        };
+        run(deps.as_mut(), "new_owner", transfer).unwrap();
+        let renounce = AccessExecuteMsg::RenounceOwnership {};
+        run(deps.as_mut(), "new_owner", renounce).unwrap();
+        assert_eq!(
+            ownership(deps.as_ref()),
+            OwnershipResponse {
+                owner: None,
+                pending_owner: None,
+            }
+        );
+        let err = run(deps.as_mut(), "owner", AccessExecuteMsg::AcceptOwnership {}).unwrap_err();
+        assert_eq!(err, AccessError::NoPendingOwner {});
+    }
+
+    #[test]
+    fn roles_are_granted_by_the_owner() {
+        let mut deps = mock_dependencies(&[]);
+        initialize_owner(&mut deps.storage, &Addr::unchecked("owner")).unwrap();
+        let guardian = Addr::unchecked("guardian");
+
+        let grant = |address: &str| AccessExecuteMsg::GrantRole {
+            role: "guardian".to_string(),
+            address: address.to_string(),
+        };
+        let err = run(deps.as_mut(), "guardian", grant("guardian")).unwrap_err();
+        assert_eq!(err, AccessError::Unauthorized {});
+        run(deps.as_mut(), "owner", grant("guardian")).unwrap();
+        run(deps.as_mut(), "owner", grant("another")).unwrap();
+
+        assert_role(&deps.storage, "guardian", &guardian).unwrap();
+        assert_role(&deps.storage, "guardian", &Addr::unchecked("owner")).unwrap();
+        let err = assert_role(&deps.storage, "publisher", &guardian).unwrap_err();
+        assert_eq!(err, AccessError::Unauthorized {});
+        assert_owner(&deps.storage, &guardian).unwrap_err();
+
+        let members = |start_after: Option<&str>| -> RoleMembersResponse {
+            let msg = AccessQueryMsg::RoleMembers {
+                role: "guardian".to_string(),
+                start_after: start_after.map(String::from),
+                limit: None,
+            };
+            from_binary(&query(deps.as_ref(), msg).unwrap()).unwrap()
+        };
+        assert_eq!(
+            members(None).members,
+            vec![Addr::unchecked("another"), guardian.clone()]
+        );
+        assert_eq!(members(Some("another")).members, vec![guardian.clone()]);
+
+        let revoke = AccessExecuteMsg::RevokeRole {
+            role: "guardian".to_string(),
+            address: "guardian".to_string(),
+        };
+        run(deps.as_mut(), "owner", revoke).unwrap();
+        assert!(!has_role(&deps.storage, "guardian", &guardian).unwrap());
+    }
+}
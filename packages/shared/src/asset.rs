@@ -0,0 +1,286 @@
+use cosmwasm_std::{
+    Addr, BankMsg, Coin, CosmosMsg, MessageInfo, QuerierWrapper, StdError, StdResult, Uint128,
+};
+use cw20::Cw20ReceiveMsg;
+use schemars::JsonSchema;
+use thiserror::Error;
+
+pub use cw_asset::{Asset, AssetInfo, AssetInfoUnchecked, AssetUnchecked};
+
+use crate::token::{TokenContract, TokenQueryError};
+
+/// Decimals of every native Terra denom
+pub const NATIVE_DECIMALS: u8 = 6;
+
+/// Why the funds received with a message don't match the expected asset
+#[derive(Error, Debug, PartialEq)]
+pub enum AssetError {
+    #[error("{0}")]
+    Std(#[from] StdError),
+
+    #[error("Expected {expected}, received nothing")]
+    NoFunds { expected: String },
+
+    #[error("Expected {expected}, received {received}")]
+    UnexpectedFunds { expected: String, received: String },
+
+    #[error("{expected} has to be sent through the token's Send message")]
+    NotNative { expected: String },
+
+    #[error("{expected} has to be sent as native funds")]
+    NotCw20 { expected: String },
+}
+
+/// Returns how much of the native asset `expected` was sent along with the
+/// message. Anything else sent along is rejected.
+pub fn must_pay_native(info: &MessageInfo, expected: &AssetInfo) -> Result<Asset, AssetError> {
+    let denom = match expected {
+        AssetInfo::Native(denom) => denom,
+        AssetInfo::Cw20(_) => {
+            return Err(AssetError::NotNative {
+                expected: expected.to_string(),
+            })
+        }
+    };
+
+    match info.funds.as_slice() {
+        [] => Err(AssetError::NoFunds {
+            expected: expected.to_string(),
+        }),
+        [coin] if coin.denom == *denom && !coin.amount.is_zero() => {
+            Ok(Asset::new(expected.clone(), coin.amount))
+        }
+        funds => Err(AssetError::UnexpectedFunds {
+            expected: expected.to_string(),
+            received: funds_label(funds),
+        }),
+    }
+}
+
+/// Returns how much of the cw20 asset `expected` came with a Receive hook.
+/// The hook must come from the token contract itself, without native funds.
+pub fn must_receive_cw20(
+    info: &MessageInfo,
+    receive: &Cw20ReceiveMsg,
+    expected: &AssetInfo,
+) -> Result<Asset, AssetError> {
+    let contract_addr = match expected {
+        AssetInfo::Cw20(contract_addr) => contract_addr,
+        AssetInfo::Native(_) => {
+            return Err(AssetError::NotCw20 {
+                expected: expected.to_string(),
+            })
+        }
+    };
+
+    if info.sender != *contract_addr || !info.funds.is_empty() {
+        let mut received = vec![format!("cw20:{}:{}", info.sender, receive.amount)];
+        if !info.funds.is_empty() {
+            received.push(funds_label(&info.funds));
+        }
+        return Err(AssetError::UnexpectedFunds {
+            expected: expected.to_string(),
+            received: received.join(","),
+        });
+    }
+    if receive.amount.is_zero() {
+        return Err(AssetError::NoFunds {
+            expected: expected.to_string(),
+        });
+    }
+    Ok(Asset::new(expected.clone(), receive.amount))
+}
+
+/// Queries the balance `account` holds of `info`. A cw20 address that isn't a
+/// token fails instead of reading as an empty balance.
+pub fn query_balance(
+    querier: &QuerierWrapper,
+    info: &AssetInfo,
+    account: &Addr,
+) -> Result<Uint128, TokenQueryError> {
+    match info {
+        AssetInfo::Native(denom) => Ok(querier.query_balance(account, denom)?.amount),
+        AssetInfo::Cw20(contract_addr) => {
+            TokenContract(contract_addr.clone()).query_balance(querier, account)
+        }
+    }
+}
+
+/// Queries how many decimals `info` has. Native denoms all have
+/// `NATIVE_DECIMALS`.
+pub fn query_decimals(querier: &QuerierWrapper, info: &AssetInfo) -> StdResult<u8> {
+    match info {
+        AssetInfo::Native(_) => Ok(NATIVE_DECIMALS),
+        AssetInfo::Cw20(contract_addr) => Ok(TokenContract(contract_addr.clone())
+            .query_info(querier)?
+            .decimals),
+    }
+}
+
+/// Message sending `asset` from the contract to `recipient`
+pub fn transfer_msg<C>(asset: &Asset, recipient: impl Into<String>) -> StdResult<CosmosMsg<C>>
+where
+    C: Clone + std::fmt::Debug + PartialEq + JsonSchema,
+{
+    match &asset.info {
+        AssetInfo::Native(denom) => Ok(BankMsg::Send {
+            to_address: recipient.into(),
+            amount: vec![Coin {
+                denom: denom.clone(),
+                amount: asset.amount,
+            }],
+        }
+        .into()),
+        AssetInfo::Cw20(contract_addr) => {
+            TokenContract(contract_addr.clone()).transfer(recipient, asset.amount)
+        }
+    }
+}
+
+fn funds_label(funds: &[Coin]) -> String {
+    funds
+        .iter()
+        .map(|coin| format!("native:{}:{}", coin.denom, coin.amount))
+        .collect::<Vec<_>>()
+        .join(",")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use cosmwasm_std::testing::{mock_info, MockQuerier};
+    use cosmwasm_std::{coin, coins, to_binary, Binary, Empty, WasmMsg};
+    use cw20::Cw20ExecuteMsg;
+
+    fn uluna() -> AssetInfo {
+        AssetInfo::Native("uluna".to_string())
+    }
+
+    fn aurm() -> AssetInfo {
+        AssetInfo::Cw20(Addr::unchecked("aurm"))
+    }
+
+    fn hook(sender: &str, amount: u128) -> Cw20ReceiveMsg {
+        Cw20ReceiveMsg {
+            sender: sender.to_string(),
+            amount: Uint128::new(amount),
+            msg: Binary::default(),
+        }
+    }
+
+    #[test]
+    fn native_payments_are_validated() {
+        let info = mock_info("buyer", &coins(100, "uluna"));
+        assert_eq!(
+            must_pay_native(&info, &uluna()).unwrap(),
+            Asset::new(uluna(), 100u128)
+        );
+
+        let err = must_pay_native(&mock_info("buyer", &[]), &uluna()).unwrap_err();
+        assert_eq!(
+            err,
+            AssetError::NoFunds {
+                expected: "native:uluna".to_string()
+            }
+        );
+
+        for funds in [
+            coins(100, "uusd"),
+            coins(0, "uluna"),
+            vec![coin(100, "uluna"), coin(1, "uusd")],
+        ] {
+            let err = must_pay_native(&mock_info("buyer", &funds), &uluna()).unwrap_err();
+            assert!(
+                matches!(err, AssetError::UnexpectedFunds { .. }),
+                "{:?}",
+                funds
+            );
+        }
+
+        let err = must_pay_native(&info, &aurm()).unwrap_err();
+        assert_eq!(
+            err,
+            AssetError::NotNative {
+                expected: "cw20:aurm".to_string()
+            }
+        );
+    }
+
+    #[test]
+    fn cw20_receipts_are_validated() {
+        let receive = hook("buyer", 100);
+        assert_eq!(
+            must_receive_cw20(&mock_info("aurm", &[]), &receive, &aurm()).unwrap(),
+            Asset::new(aurm(), 100u128)
+        );
+
+        // Anyone can call Receive directly, only the token itself is trusted
+        let err = must_receive_cw20(&mock_info("buyer", &[]), &receive, &aurm()).unwrap_err();
+        assert_eq!(
+            err,
+            AssetError::UnexpectedFunds {
+                expected: "cw20:aurm".to_string(),
+                received: "cw20:buyer:100".to_string(),
+            }
+        );
+
+        let info = mock_info("aurm", &coins(5, "uluna"));
+        let err = must_receive_cw20(&info, &receive, &aurm()).unwrap_err();
+        assert_eq!(
+            err,
+            AssetError::UnexpectedFunds {
+                expected: "cw20:aurm".to_string(),
+                received: "cw20:aurm:100,native:uluna:5".to_string(),
+            }
+        );
+
+        let err =
+            must_receive_cw20(&mock_info("aurm", &[]), &hook("buyer", 0), &aurm()).unwrap_err();
+        assert!(matches!(err, AssetError::NoFunds { .. }));
+
+        let err = must_receive_cw20(&mock_info("aurm", &[]), &receive, &uluna()).unwrap_err();
+        assert!(matches!(err, AssetError::NotCw20 { .. }));
+    }
+
+    #[test]
+    fn transfers_and_balances_cover_both_kinds() {
+        let native: CosmosMsg<Empty> = transfer_msg(&Asset::new(uluna(), 7u128), "buyer").unwrap();
+        assert_eq!(
+            native,
+            CosmosMsg::Bank(BankMsg::Send {
+                to_address: "buyer".to_string(),
+                amount: coins(7, "uluna"),
+            })
+        );
+
+        let cw20: CosmosMsg<Empty> = transfer_msg(&Asset::new(aurm(), 7u128), "buyer").unwrap();
+        assert_eq!(
+            cw20,
+            CosmosMsg::Wasm(WasmMsg::Execute {
+                contract_addr: "aurm".to_string(),
+                msg: to_binary(&Cw20ExecuteMsg::Transfer {
+                    recipient: "buyer".to_string(),
+                    amount: Uint128::new(7),
+                })
+                .unwrap(),
+                funds: vec![],
+            })
+        );
+
+        let querier: MockQuerier = MockQuerier::new(&[("swap", &coins(42, "uluna"))]);
+        let querier = QuerierWrapper::new(&querier);
+        let swap = Addr::unchecked("swap");
+        assert_eq!(
+            query_balance(&querier, &uluna(), &swap).unwrap(),
+            Uint128::new(42)
+        );
+        // The mock has no contracts, a cw20 asset must not read as empty
+        let err = query_balance(&querier, &aurm(), &swap).unwrap_err();
+        assert_eq!(
+            err,
+            TokenQueryError::ContractNotFound {
+                contract_addr: "aurm".to_string()
+            }
+        );
+    }
+}
@@ -1,2 +1,13 @@
+pub mod access;
+pub mod asset;
+pub mod math;
+#[cfg(feature = "testing")]
+pub mod mock_app;
+#[cfg(feature = "testing")]
+pub mod mock_querier;
 pub mod oracle;
 pub mod querier;
+pub mod swap;
+#[cfg(feature = "testing")]
+pub mod testing;
+pub mod token;
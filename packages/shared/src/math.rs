@@ -0,0 +1,363 @@
+use cosmwasm_std::{
+    Decimal, DivideByZeroError, Fraction, OverflowError, OverflowOperation, StdError, StdResult,
+    Uint128,
+};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+#[allow(clippy::all)]
+mod uints {
+    uint::construct_uint! {
+        pub struct U256(4);
+    }
+}
+
+pub use uints::U256;
+
+// How a result that falls between two base units is rounded. HalfEven rounds
+// to the nearest unit and breaks ties towards the even one.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum Rounding {
+    Floor,
+    Ceil,
+    HalfEven,
+}
+
+/// Returns `value * numerator / denominator`. The product is kept in 256 bits,
+/// so only the final result has to fit in a [`Uint128`], and it is rounded
+/// once at the end.
+pub fn mul_div(
+    value: Uint128,
+    numerator: Uint128,
+    denominator: Uint128,
+    rounding: Rounding,
+) -> StdResult<Uint128> {
+    mul_div_wide(
+        U256::from(value.u128()),
+        U256::from(numerator.u128()),
+        U256::from(denominator.u128()),
+        rounding,
+    )
+}
+
+/// Returns `amount * rate`
+pub fn mul_decimal(amount: Uint128, rate: Decimal, rounding: Rounding) -> StdResult<Uint128> {
+    mul_div_wide(
+        U256::from(amount.u128()),
+        U256::from(rate.numerator()),
+        U256::from(rate.denominator()),
+        rounding,
+    )
+}
+
+/// Returns `amount / rate`, failing on a zero rate
+pub fn div_decimal(amount: Uint128, rate: Decimal, rounding: Rounding) -> StdResult<Uint128> {
+    mul_div_wide(
+        U256::from(amount.u128()),
+        U256::from(rate.denominator()),
+        U256::from(rate.numerator()),
+        rounding,
+    )
+}
+
+/// Re-expresses `amount` base units of a token with `from_decimals` decimals
+/// in base units of `to_decimals` decimals
+pub fn convert_decimals(
+    amount: Uint128,
+    from_decimals: u8,
+    to_decimals: u8,
+    rounding: Rounding,
+) -> StdResult<Uint128> {
+    let (numerator, denominator) = decimal_shift(from_decimals, to_decimals)?;
+    mul_div_wide(U256::from(amount.u128()), numerator, denominator, rounding)
+}
+
+/// Values `amount` base units of a token with `amount_decimals` decimals at
+/// `price`, whole result units per whole token, in base units of a token with
+/// `result_decimals` decimals
+pub fn mul_price(
+    amount: Uint128,
+    amount_decimals: u8,
+    price: Decimal,
+    result_decimals: u8,
+    rounding: Rounding,
+) -> StdResult<Uint128> {
+    let (shift_numerator, shift_denominator) = decimal_shift(amount_decimals, result_decimals)?;
+    let numerator = checked_mul(U256::from(price.numerator()), shift_numerator)?;
+    let denominator = checked_mul(U256::from(price.denominator()), shift_denominator)?;
+    mul_div_wide(U256::from(amount.u128()), numerator, denominator, rounding)
+}
+
+/// Inverse of [`mul_price`]: how many base units of a token with
+/// `result_decimals` decimals `amount` buys at `price`, whole `amount` units
+/// per whole token
+pub fn div_price(
+    amount: Uint128,
+    amount_decimals: u8,
+    price: Decimal,
+    result_decimals: u8,
+    rounding: Rounding,
+) -> StdResult<Uint128> {
+    let (shift_numerator, shift_denominator) = decimal_shift(amount_decimals, result_decimals)?;
+    let numerator = checked_mul(U256::from(price.denominator()), shift_numerator)?;
+    let denominator = checked_mul(U256::from(price.numerator()), shift_denominator)?;
+    mul_div_wide(U256::from(amount.u128()), numerator, denominator, rounding)
+}
+
+// Factor taking base units with `from` decimals to base units with `to`
+// decimals, as a fraction
+fn decimal_shift(from: u8, to: u8) -> StdResult<(U256, U256)> {
+    let power = |exponent: u8| {
+        U256::from(10)
+            .checked_pow(U256::from(exponent))
+            .ok_or_else(|| overflow(OverflowOperation::Pow, 10, exponent))
+    };
+    if to >= from {
+        Ok((power(to - from)?, U256::one()))
+    } else {
+        Ok((U256::one(), power(from - to)?))
+    }
+}
+
+fn mul_div_wide(
+    value: U256,
+    numerator: U256,
+    denominator: U256,
+    rounding: Rounding,
+) -> StdResult<Uint128> {
+    if denominator.is_zero() {
+        return Err(StdError::divide_by_zero(DivideByZeroError::new(value)));
+    }
+    let (quotient, remainder) = checked_mul(value, numerator)?.div_mod(denominator);
+
+    let round_up = match rounding {
+        Rounding::Floor => false,
+        Rounding::Ceil => !remainder.is_zero(),
+        // Compares the remainder to half the denominator without doubling it,
+        // which could overflow
+        Rounding::HalfEven => {
+            let rest = denominator - remainder;
+            remainder > rest || (remainder == rest && quotient.bit(0))
+        }
+    };
+    // Can't overflow, the quotient is at most the product divided by one
+    let result = if round_up { quotient + 1 } else { quotient };
+
+    if result > U256::from(u128::MAX) {
+        return Err(overflow(OverflowOperation::Mul, value, numerator));
+    }
+    Ok(Uint128::new(result.low_u128()))
+}
+
+fn checked_mul(a: U256, b: U256) -> StdResult<U256> {
+    a.checked_mul(b)
+        .ok_or_else(|| overflow(OverflowOperation::Mul, a, b))
+}
+
+fn overflow(operation: OverflowOperation, a: impl ToString, b: impl ToString) -> StdError {
+    StdError::overflow(OverflowError::new(operation, a, b))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::str::FromStr;
+
+    const ROUNDINGS: [Rounding; 3] = [Rounding::Floor, Rounding::Ceil, Rounding::HalfEven];
+
+    // Rounds `dividend / divisor` with plain u128 arithmetic, for inputs small
+    // enough not to overflow
+    fn reference(dividend: u128, divisor: u128, rounding: Rounding) -> u128 {
+        let (quotient, remainder) = (dividend / divisor, dividend % divisor);
+        let round_up = match rounding {
+            Rounding::Floor => false,
+            Rounding::Ceil => remainder > 0,
+            Rounding::HalfEven => {
+                2 * remainder > divisor || (2 * remainder == divisor && quotient % 2 == 1)
+            }
+        };
+        quotient + round_up as u128
+    }
+
+    // xorshift64*, enough to spread inputs over the whole u128 range
+    struct Inputs(u64);
+
+    impl Inputs {
+        fn next_u64(&mut self) -> u64 {
+            self.0 ^= self.0 >> 12;
+            self.0 ^= self.0 << 25;
+            self.0 ^= self.0 >> 27;
+            self.0.wrapping_mul(0x2545_f491_4f6c_dd1d)
+        }
+
+        // Mixes in small magnitudes so that edge cases around zero and
+        // exact division come up often
+        fn next_u128(&mut self) -> u128 {
+            let value = (self.next_u64() as u128) << 64 | self.next_u64() as u128;
+            value >> (self.next_u64() % 128)
+        }
+    }
+
+    #[test]
+    fn mul_div_matches_reference_on_small_inputs() {
+        for value in 0..=40u128 {
+            for numerator in 0..=40u128 {
+                for denominator in 1..=40u128 {
+                    for rounding in ROUNDINGS {
+                        let result = mul_div(
+                            Uint128::new(value),
+                            Uint128::new(numerator),
+                            Uint128::new(denominator),
+                            rounding,
+                        )
+                        .unwrap();
+                        let expected = reference(value * numerator, denominator, rounding);
+                        assert_eq!(
+                            Uint128::new(expected),
+                            result,
+                            "{} * {} / {} rounding {:?}",
+                            value,
+                            numerator,
+                            denominator,
+                            rounding
+                        );
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn mul_div_rounding_properties_on_wide_inputs() {
+        let mut inputs = Inputs(0x9e37_79b9_7f4a_7c15);
+        for _ in 0..20_000 {
+            let (value, numerator) = (inputs.next_u128(), inputs.next_u128());
+            let denominator = inputs.next_u128().max(1);
+            let args = (
+                Uint128::new(value),
+                Uint128::new(numerator),
+                Uint128::new(denominator),
+            );
+            let results = ROUNDINGS.map(|rounding| mul_div(args.0, args.1, args.2, rounding));
+
+            let product = U256::from(value) * U256::from(numerator);
+            let exact = product % U256::from(denominator) == U256::zero();
+            let floor = match &results[0] {
+                Ok(floor) => U256::from(floor.u128()),
+                // Overflow is only reported when the result doesn't fit
+                Err(StdError::Overflow { .. }) => {
+                    assert!(product / U256::from(denominator) > U256::from(u128::MAX));
+                    continue;
+                }
+                Err(err) => panic!("unexpected error {}", err),
+            };
+            // floor <= value * numerator / denominator < floor + 1
+            assert!(floor * U256::from(denominator) <= product);
+            assert!((floor + 1) * U256::from(denominator) > product);
+
+            match (&results[1], &results[2]) {
+                (Ok(ceil), Ok(half_even)) => {
+                    let (ceil, half_even) = (U256::from(ceil.u128()), U256::from(half_even.u128()));
+                    assert_eq!(if exact { floor } else { floor + 1 }, ceil);
+                    assert!(half_even == floor || half_even == ceil);
+                }
+                // Rounding up may push the largest results over the edge
+                _ => assert_eq!(U256::from(u128::MAX), floor),
+            }
+        }
+    }
+
+    #[test]
+    fn half_even_breaks_ties_to_even() {
+        let half = |value: u128| {
+            mul_div(
+                Uint128::new(value),
+                Uint128::new(1),
+                Uint128::new(2),
+                Rounding::HalfEven,
+            )
+            .unwrap()
+        };
+        assert_eq!(Uint128::new(0), half(1));
+        assert_eq!(Uint128::new(2), half(3));
+        assert_eq!(Uint128::new(2), half(5));
+        assert_eq!(Uint128::new(4), half(7));
+    }
+
+    #[test]
+    fn intermediate_products_use_256_bits() {
+        let max = Uint128::new(u128::MAX);
+        assert_eq!(max, mul_div(max, max, max, Rounding::Floor).unwrap());
+
+        let err = mul_div(max, Uint128::new(2), Uint128::new(1), Rounding::Floor).unwrap_err();
+        assert!(matches!(err, StdError::Overflow { .. }));
+        let err = mul_div(max, max, Uint128::zero(), Rounding::Floor).unwrap_err();
+        assert!(matches!(err, StdError::DivideByZero { .. }));
+        let err = div_decimal(max, Decimal::zero(), Rounding::Floor).unwrap_err();
+        assert!(matches!(err, StdError::DivideByZero { .. }));
+    }
+
+    #[test]
+    fn decimal_rates_and_token_decimals() {
+        let rate = Decimal::from_str("0.3").unwrap();
+        let amount = Uint128::new(10);
+        assert_eq!(
+            Uint128::new(3),
+            mul_decimal(amount, rate, Rounding::HalfEven).unwrap()
+        );
+        assert_eq!(
+            Uint128::new(33),
+            div_decimal(amount, rate, Rounding::Floor).unwrap()
+        );
+        assert_eq!(
+            Uint128::new(34),
+            div_decimal(amount, rate, Rounding::Ceil).unwrap()
+        );
+
+        let amount = Uint128::new(1_234_500);
+        for (rounding, expected) in [
+            (Rounding::Floor, 1234),
+            (Rounding::Ceil, 1235),
+            (Rounding::HalfEven, 1234),
+        ] {
+            let result = convert_decimals(amount, 6, 3, rounding).unwrap();
+            assert_eq!(Uint128::new(expected), result);
+        }
+        assert_eq!(
+            Uint128::new(1_234_500_000_000_000_000),
+            convert_decimals(amount, 6, 18, Rounding::Floor).unwrap()
+        );
+
+        // 2.5 whole units of a 6 decimal token at 4 each, in an 18 decimal token
+        let price = Decimal::from_ratio(4u128, 1u128);
+        let value = mul_price(Uint128::new(2_500_000), 6, price, 18, Rounding::Floor).unwrap();
+        assert_eq!(Uint128::new(10_000_000_000_000_000_000), value);
+        let amount = div_price(value, 18, price, 6, Rounding::Floor).unwrap();
+        assert_eq!(Uint128::new(2_500_000), amount);
+    }
+
+    #[test]
+    fn pricing_round_trips_never_create_value() {
+        let mut inputs = Inputs(0x2545_f491_4f6c_dd1d);
+        for _ in 0..5_000 {
+            let amount = Uint128::new(inputs.next_u128() >> 32);
+            let price = Decimal::from_ratio(inputs.next_u64().max(1), inputs.next_u64().max(1));
+            let (from, to) = (
+                (inputs.next_u64() % 19) as u8,
+                (inputs.next_u64() % 19) as u8,
+            );
+
+            let value = match mul_price(amount, from, price, to, Rounding::Floor) {
+                Ok(value) => value,
+                Err(_) => continue,
+            };
+            // Buying back with the floored value never returns more than was paid
+            if let Ok(back) = div_price(value, to, price, from, Rounding::Floor) {
+                assert!(back <= amount, "{} -> {} -> {}", amount, value, back);
+            }
+            let ceil = mul_price(amount, from, price, to, Rounding::Ceil).unwrap();
+            assert!(ceil >= value && ceil - value <= Uint128::new(1));
+        }
+    }
+}
@@ -0,0 +1,257 @@
+use std::fmt::Display;
+
+use anyhow::{anyhow, bail, Result as AnyResult};
+use cosmwasm_std::testing::{mock_env, mock_info, MockApi, MockStorage};
+use cosmwasm_std::{
+    from_binary, Addr, Attribute, BankMsg, Coin, CosmosMsg, DepsMut, DistributionMsg, Env,
+    FullDelegation, MessageInfo, Order, OwnedDeps, Response, StakingMsg, Storage, Uint128, WasmMsg,
+};
+use serde::de::DeserializeOwned;
+use terra_cosmwasm::{TerraMsg, TerraMsgWrapper};
+
+use crate::mock_querier::{mock_dependencies, TerraMockQuerier, LUNA_DENOM};
+
+pub type ExecuteFn<T, E> = fn(DepsMut, Env, MessageInfo, T) -> Result<Response<TerraMsgWrapper>, E>;
+
+/// What a call left behind: the attributes of every contract execution in
+/// order, and the messages the app doesn't execute, e.g. calls to other
+/// contracts
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct AppResponse {
+    pub attributes: Vec<Attribute>,
+    pub unhandled: Vec<CosmosMsg<TerraMsgWrapper>>,
+}
+
+/// Runs a single Terra contract on a `TerraMockQuerier`. Bank transfers,
+/// market swaps, delegations and reward withdrawals are applied to the
+/// querier's balances, and calls the contract makes to itself are executed
+/// right away. Like a transaction, a failed call changes nothing.
+pub struct TerraApp<T, E> {
+    pub deps: OwnedDeps<MockStorage, MockApi, TerraMockQuerier>,
+    pub env: Env,
+    execute_fn: ExecuteFn<T, E>,
+}
+
+impl<T, E> TerraApp<T, E>
+where
+    T: DeserializeOwned,
+    E: Display,
+{
+    pub fn new(execute_fn: ExecuteFn<T, E>) -> Self {
+        TerraApp {
+            deps: mock_dependencies(&[]),
+            env: mock_env(),
+            execute_fn,
+        }
+    }
+
+    pub fn querier(&mut self) -> &mut TerraMockQuerier {
+        &mut self.deps.querier
+    }
+
+    pub fn balance(&self, addr: &str, denom: &str) -> Uint128 {
+        self.deps.querier.balance(addr, denom)
+    }
+
+    /// Executes `msg` as `sender`, who has to hold `funds`
+    pub fn execute(&mut self, sender: &str, funds: &[Coin], msg: T) -> AnyResult<AppResponse> {
+        let querier = self.deps.querier.clone();
+        let storage: Vec<_> = self
+            .deps
+            .storage
+            .range(None, None, Order::Ascending)
+            .collect();
+
+        let mut res = AppResponse::default();
+        let result = self.call(sender, funds, msg, &mut res);
+        if result.is_err() {
+            self.deps.querier = querier;
+            self.deps.storage = MockStorage::default();
+            for (key, value) in storage {
+                self.deps.storage.set(&key, &value);
+            }
+        }
+        result.map(|_| res)
+    }
+
+    fn call(
+        &mut self,
+        sender: &str,
+        funds: &[Coin],
+        msg: T,
+        res: &mut AppResponse,
+    ) -> AnyResult<()> {
+        let contract = self.env.contract.address.to_string();
+        transfer(&mut self.deps.querier, sender, &contract, funds)?;
+
+        let info = mock_info(sender, funds);
+        let response = (self.execute_fn)(self.deps.as_mut(), self.env.clone(), info, msg)
+            .map_err(|e| anyhow!("{}", e))?;
+        res.attributes.extend(response.attributes);
+        for sub_msg in response.messages {
+            self.dispatch(sub_msg.msg, res)?;
+        }
+        Ok(())
+    }
+
+    fn dispatch(
+        &mut self,
+        msg: CosmosMsg<TerraMsgWrapper>,
+        res: &mut AppResponse,
+    ) -> AnyResult<()> {
+        let contract = self.env.contract.address.to_string();
+        let querier = &mut self.deps.querier;
+        match msg {
+            CosmosMsg::Bank(BankMsg::Send { to_address, amount }) => {
+                transfer(querier, &contract, &to_address, &amount)
+            }
+            CosmosMsg::Custom(TerraMsgWrapper { msg_data, .. }) => match msg_data {
+                TerraMsg::Swap {
+                    offer_coin,
+                    ask_denom,
+                } => swap(querier, &contract, &contract, &offer_coin, &ask_denom),
+                TerraMsg::SwapSend {
+                    to_address,
+                    offer_coin,
+                    ask_denom,
+                } => swap(querier, &contract, &to_address, &offer_coin, &ask_denom),
+            },
+            CosmosMsg::Staking(StakingMsg::Delegate { validator, amount }) => {
+                delegate(querier, &contract, &validator, &amount)
+            }
+            CosmosMsg::Staking(StakingMsg::Undelegate { validator, amount }) => {
+                undelegate(querier, &contract, &validator, &amount)
+            }
+            CosmosMsg::Distribution(DistributionMsg::WithdrawDelegatorReward { validator }) => {
+                withdraw_rewards(querier, &contract, &validator)
+            }
+            CosmosMsg::Wasm(WasmMsg::Execute {
+                contract_addr,
+                msg,
+                funds,
+            }) if contract_addr == contract => {
+                self.call(&contract, &funds, from_binary(&msg)?, res)
+            }
+            msg => {
+                res.unhandled.push(msg);
+                Ok(())
+            }
+        }
+    }
+}
+
+fn add(querier: &mut TerraMockQuerier, addr: &str, coin: &Coin) {
+    let balance = querier.balances.entry(addr.to_string()).or_default();
+    match balance.iter_mut().find(|c| c.denom == coin.denom) {
+        Some(c) => c.amount += coin.amount,
+        None => balance.push(coin.clone()),
+    }
+}
+
+fn sub(querier: &mut TerraMockQuerier, addr: &str, coin: &Coin) -> AnyResult<()> {
+    let held = querier.balance(addr, &coin.denom);
+    if held < coin.amount {
+        bail!("{} holds {}{}, needs {}", addr, held, coin.denom, coin);
+    }
+    let balance = querier.balances.get_mut(addr).unwrap();
+    for c in balance.iter_mut().filter(|c| c.denom == coin.denom) {
+        c.amount -= coin.amount;
+    }
+    balance.retain(|c| !c.amount.is_zero());
+    Ok(())
+}
+
+fn transfer(querier: &mut TerraMockQuerier, from: &str, to: &str, coins: &[Coin]) -> AnyResult<()> {
+    for coin in coins {
+        sub(querier, from, coin)?;
+        add(querier, to, coin);
+    }
+    Ok(())
+}
+
+fn swap(
+    querier: &mut TerraMockQuerier,
+    trader: &str,
+    recipient: &str,
+    offer_coin: &Coin,
+    ask_denom: &str,
+) -> AnyResult<()> {
+    let receive = querier.simulate_swap(offer_coin, ask_denom)?;
+    sub(querier, trader, offer_coin)?;
+    add(querier, recipient, &receive);
+    Ok(())
+}
+
+fn delegate(
+    querier: &mut TerraMockQuerier,
+    delegator: &str,
+    validator: &str,
+    amount: &Coin,
+) -> AnyResult<()> {
+    if amount.denom != LUNA_DENOM {
+        bail!("only {} can be delegated", LUNA_DENOM);
+    }
+    if !querier.validators.iter().any(|v| v.address == validator) {
+        bail!("{} is not an active validator", validator);
+    }
+    sub(querier, delegator, amount)?;
+    let existing = querier
+        .delegations
+        .iter_mut()
+        .find(|d| d.delegator == delegator && d.validator == validator);
+    match existing {
+        Some(delegation) => {
+            delegation.amount.amount += amount.amount;
+            delegation.can_redelegate.amount += amount.amount;
+        }
+        None => querier.delegations.push(FullDelegation {
+            delegator: Addr::unchecked(delegator),
+            validator: validator.to_string(),
+            amount: amount.clone(),
+            can_redelegate: amount.clone(),
+            accumulated_rewards: vec![],
+        }),
+    }
+    Ok(())
+}
+
+// Undelegated stake is unbonding and never returned, as no time passes
+fn undelegate(
+    querier: &mut TerraMockQuerier,
+    delegator: &str,
+    validator: &str,
+    amount: &Coin,
+) -> AnyResult<()> {
+    let delegation = querier
+        .delegations
+        .iter_mut()
+        .find(|d| d.delegator == delegator && d.validator == validator)
+        .ok_or_else(|| anyhow!("{} has no delegation to {}", delegator, validator))?;
+    if delegation.amount.denom != amount.denom || delegation.amount.amount < amount.amount {
+        bail!("cannot undelegate {} of {}", amount, delegation.amount);
+    }
+    delegation.amount.amount -= amount.amount;
+    delegation.can_redelegate.amount = delegation
+        .can_redelegate
+        .amount
+        .min(delegation.amount.amount);
+    querier.delegations.retain(|d| !d.amount.amount.is_zero());
+    Ok(())
+}
+
+fn withdraw_rewards(
+    querier: &mut TerraMockQuerier,
+    delegator: &str,
+    validator: &str,
+) -> AnyResult<()> {
+    let delegation = querier
+        .delegations
+        .iter_mut()
+        .find(|d| d.delegator == delegator && d.validator == validator)
+        .ok_or_else(|| anyhow!("{} has no delegation to {}", delegator, validator))?;
+    let rewards = std::mem::take(&mut delegation.accumulated_rewards);
+    for coin in &rewards {
+        add(querier, delegator, coin);
+    }
+    Ok(())
+}
@@ -0,0 +1,399 @@
+use std::collections::BTreeMap;
+
+use cosmwasm_std::testing::{MockApi, MockStorage, StakingQuerier, MOCK_CONTRACT_ADDR};
+use cosmwasm_std::{
+    from_slice, to_binary, AllBalanceResponse, BalanceResponse, BankQuery, Binary, Coin,
+    ContractResult, Decimal, Fraction, FullDelegation, OwnedDeps, Querier, QuerierResult,
+    QueryRequest, StdError, StdResult, SystemError, SystemResult, Uint128, Validator, WasmQuery,
+};
+use cw20::{BalanceResponse as Cw20BalanceResponse, Cw20QueryMsg, TokenInfoResponse};
+use serde::Serialize;
+use terra_cosmwasm::{
+    ExchangeRateItem, ExchangeRatesResponse, SwapResponse, TaxCapResponse, TaxRateResponse,
+    TerraQuery, TerraQueryWrapper,
+};
+
+use crate::math::{mul_div, Rounding};
+use crate::oracle::{FeedId, PriceResponse, QueryMsg as OracleQueryMsg};
+
+/// Staking denom, and the denom exchange rates are quoted against
+pub const LUNA_DENOM: &str = "uluna";
+
+/// Like `cosmwasm_std::testing::mock_dependencies`, but with a querier that
+/// also answers Terra queries
+pub fn mock_dependencies(
+    contract_balance: &[Coin],
+) -> OwnedDeps<MockStorage, MockApi, TerraMockQuerier> {
+    OwnedDeps {
+        storage: MockStorage::default(),
+        api: MockApi::default(),
+        querier: TerraMockQuerier::new(&[(MOCK_CONTRACT_ADDR, contract_balance)]),
+    }
+}
+
+/// Answers bank, staking, Terra and wasm smart queries from fixtures.
+/// Exchange rates and tax caps that aren't set fail like unknown denoms do on
+/// chain, and smart queries without a fixture fail as unknown contracts.
+#[derive(Clone, Debug, Default)]
+pub struct TerraMockQuerier {
+    pub(crate) balances: BTreeMap<String, Vec<Coin>>,
+    // Price of one uluna in each denom, as kept by Terra's oracle module
+    pub(crate) exchange_rates: BTreeMap<String, Decimal>,
+    pub(crate) tax_rate: Decimal,
+    pub(crate) tax_caps: BTreeMap<String, Uint128>,
+    pub(crate) validators: Vec<Validator>,
+    pub(crate) delegations: Vec<FullDelegation>,
+    smart_queries: BTreeMap<(String, Binary), Binary>,
+}
+
+impl TerraMockQuerier {
+    pub fn new(balances: &[(&str, &[Coin])]) -> Self {
+        let mut querier = TerraMockQuerier::default();
+        for (addr, balance) in balances {
+            querier.update_balance(*addr, balance.to_vec());
+        }
+        querier
+    }
+
+    pub fn update_balance(&mut self, addr: impl Into<String>, balance: Vec<Coin>) {
+        self.balances.insert(addr.into(), balance);
+    }
+
+    /// Sets the price of one uluna in `denom`
+    pub fn update_exchange_rate(&mut self, denom: impl Into<String>, rate: Decimal) {
+        self.exchange_rates.insert(denom.into(), rate);
+    }
+
+    pub fn update_tax(&mut self, rate: Decimal, caps: &[(&str, u128)]) {
+        self.tax_rate = rate;
+        self.tax_caps = caps
+            .iter()
+            .map(|(denom, cap)| (denom.to_string(), Uint128::new(*cap)))
+            .collect();
+    }
+
+    /// Sets the active validators and the existing delegations, uluna is the
+    /// bonded denom
+    pub fn update_staking(&mut self, validators: &[Validator], delegations: &[FullDelegation]) {
+        self.validators = validators.to_vec();
+        self.delegations = delegations.to_vec();
+    }
+
+    /// Sets the rewards `delegator` can withdraw from its delegation to
+    /// `validator`
+    pub fn update_rewards(&mut self, delegator: &str, validator: &str, rewards: Vec<Coin>) {
+        let delegation = self
+            .delegations
+            .iter_mut()
+            .find(|d| d.delegator == delegator && d.validator == validator)
+            .expect("no such delegation");
+        delegation.accumulated_rewards = rewards;
+    }
+
+    /// Answers `msg` sent to `contract` with `response`
+    pub fn update_smart_query(
+        &mut self,
+        contract: impl Into<String>,
+        msg: &impl Serialize,
+        response: &impl Serialize,
+    ) {
+        self.smart_queries.insert(
+            (contract.into(), to_binary(msg).unwrap()),
+            to_binary(response).unwrap(),
+        );
+    }
+
+    /// Answers cw20 balance queries for `account` on `token`
+    pub fn update_token_balance(
+        &mut self,
+        token: impl Into<String>,
+        account: impl Into<String>,
+        balance: u128,
+    ) {
+        let msg = Cw20QueryMsg::Balance {
+            address: account.into(),
+        };
+        let response = Cw20BalanceResponse {
+            balance: Uint128::new(balance),
+        };
+        self.update_smart_query(token, &msg, &response);
+    }
+
+    /// Answers cw20 token info queries on `token`, with `decimals` decimals
+    pub fn update_token_decimals(&mut self, token: impl Into<String>, decimals: u8) {
+        let token = token.into();
+        let response = TokenInfoResponse {
+            name: token.clone(),
+            symbol: token.to_uppercase(),
+            decimals,
+            total_supply: Uint128::zero(),
+        };
+        self.update_smart_query(token, &Cw20QueryMsg::TokenInfo {}, &response);
+    }
+
+    /// Answers `OracleContract::query_price` on `oracle` for `feed`, with the
+    /// oracle's default age and kind
+    pub fn update_oracle_price(
+        &mut self,
+        oracle: impl Into<String>,
+        feed: Option<FeedId>,
+        price: &PriceResponse,
+    ) {
+        let msg = OracleQueryMsg::QueryPriceV2 {
+            feed,
+            max_age_seconds: None,
+            kind: None,
+        };
+        self.update_smart_query(oracle, &msg, price);
+    }
+
+    pub fn balance(&self, addr: &str, denom: &str) -> Uint128 {
+        self.balances
+            .get(addr)
+            .and_then(|balance| balance.iter().find(|coin| coin.denom == denom))
+            .map(|coin| coin.amount)
+            .unwrap_or_default()
+    }
+
+    /// How much `quote_denom` one `base_denom` is worth
+    pub fn exchange_rate(&self, base_denom: &str, quote_denom: &str) -> StdResult<Decimal> {
+        let base = self.luna_rate(base_denom)?;
+        let quote = self.luna_rate(quote_denom)?;
+        Ok(Decimal::from_ratio(quote.numerator(), base.numerator()))
+    }
+
+    /// What a market swap of `offer_coin` returns, at the exchange rates
+    /// without any spread
+    pub fn simulate_swap(&self, offer_coin: &Coin, ask_denom: &str) -> StdResult<Coin> {
+        if offer_coin.denom == ask_denom {
+            return Err(StdError::generic_err("recursive swap"));
+        }
+        let offer = self.luna_rate(&offer_coin.denom)?;
+        let ask = self.luna_rate(ask_denom)?;
+        let amount = mul_div(
+            offer_coin.amount,
+            ask.numerator().into(),
+            offer.numerator().into(),
+            Rounding::Floor,
+        )?;
+        Ok(Coin {
+            denom: ask_denom.to_string(),
+            amount,
+        })
+    }
+
+    fn luna_rate(&self, denom: &str) -> StdResult<Decimal> {
+        if denom == LUNA_DENOM {
+            return Ok(Decimal::one());
+        }
+        self.exchange_rates
+            .get(denom)
+            .copied()
+            .ok_or_else(|| StdError::generic_err(format!("no exchange rate for {}", denom)))
+    }
+
+    fn terra_query(&self, query: &TerraQuery) -> StdResult<Binary> {
+        match query {
+            TerraQuery::Swap {
+                offer_coin,
+                ask_denom,
+            } => to_binary(&SwapResponse {
+                receive: self.simulate_swap(offer_coin, ask_denom)?,
+            }),
+            TerraQuery::TaxRate {} => to_binary(&TaxRateResponse {
+                rate: self.tax_rate,
+            }),
+            TerraQuery::TaxCap { denom } => {
+                let cap =
+                    self.tax_caps.get(denom).copied().ok_or_else(|| {
+                        StdError::generic_err(format!("no tax cap for {}", denom))
+                    })?;
+                to_binary(&TaxCapResponse { cap })
+            }
+            TerraQuery::ExchangeRates {
+                base_denom,
+                quote_denoms,
+            } => {
+                let exchange_rates = quote_denoms
+                    .iter()
+                    .map(|quote_denom| {
+                        Ok(ExchangeRateItem {
+                            quote_denom: quote_denom.clone(),
+                            exchange_rate: self.exchange_rate(base_denom, quote_denom)?,
+                        })
+                    })
+                    .collect::<StdResult<_>>()?;
+                to_binary(&ExchangeRatesResponse {
+                    base_denom: base_denom.clone(),
+                    exchange_rates,
+                })
+            }
+            TerraQuery::ContractInfo { .. } => {
+                Err(StdError::generic_err("contract info is not mocked"))
+            }
+        }
+    }
+
+    fn bank_query(&self, query: &BankQuery) -> QuerierResult {
+        let res = match query {
+            BankQuery::Balance { address, denom } => to_binary(&BalanceResponse {
+                amount: Coin {
+                    denom: denom.clone(),
+                    amount: self.balance(address, denom),
+                },
+            }),
+            BankQuery::AllBalances { address } => to_binary(&AllBalanceResponse {
+                amount: self.balances.get(address).cloned().unwrap_or_default(),
+            }),
+            _ => {
+                return SystemResult::Err(SystemError::UnsupportedRequest {
+                    kind: "bank".to_string(),
+                })
+            }
+        };
+        SystemResult::Ok(res.into())
+    }
+
+    fn smart_query(&self, contract_addr: &str, msg: &Binary) -> QuerierResult {
+        let key = (contract_addr.to_string(), msg.clone());
+        match self.smart_queries.get(&key) {
+            Some(response) => SystemResult::Ok(ContractResult::Ok(response.clone())),
+            None if self
+                .smart_queries
+                .keys()
+                .any(|(contract, _)| contract == contract_addr) =>
+            {
+                SystemResult::Ok(ContractResult::Err(format!(
+                    "no fixture for query {}",
+                    String::from_utf8_lossy(msg.as_slice())
+                )))
+            }
+            None => SystemResult::Err(SystemError::NoSuchContract {
+                addr: contract_addr.to_string(),
+            }),
+        }
+    }
+}
+
+impl Querier for TerraMockQuerier {
+    fn raw_query(&self, bin_request: &[u8]) -> QuerierResult {
+        let request: QueryRequest<TerraQueryWrapper> = match from_slice(bin_request) {
+            Ok(request) => request,
+            Err(e) => {
+                return SystemResult::Err(SystemError::InvalidRequest {
+                    error: format!("Parsing query request: {}", e),
+                    request: bin_request.into(),
+                })
+            }
+        };
+        match &request {
+            QueryRequest::Bank(query) => self.bank_query(query),
+            QueryRequest::Custom(TerraQueryWrapper { query_data, .. }) => {
+                SystemResult::Ok(self.terra_query(query_data).into())
+            }
+            QueryRequest::Staking(query) => {
+                StakingQuerier::new(LUNA_DENOM, &self.validators, &self.delegations).query(query)
+            }
+            QueryRequest::Wasm(WasmQuery::Smart { contract_addr, msg }) => {
+                self.smart_query(contract_addr, msg)
+            }
+            _ => SystemResult::Err(SystemError::UnsupportedRequest {
+                kind: "unsupported query".to_string(),
+            }),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use cosmwasm_std::{coin, coins, Addr, QuerierWrapper};
+    use terra_cosmwasm::TerraQuerier;
+
+    use crate::token::{TokenContract, TokenQueryError};
+
+    #[test]
+    fn answers_terra_queries_from_fixtures() {
+        let mut querier = TerraMockQuerier::new(&[("alice", &coins(5, "uusd"))]);
+        querier.update_exchange_rate("uusd", Decimal::from_ratio(40u128, 1u128));
+        querier.update_exchange_rate("ukrw", Decimal::from_ratio(48000u128, 1u128));
+        querier.update_tax(Decimal::permille(5), &[("uusd", 1_000_000)]);
+        let wrapper = QuerierWrapper::new(&querier);
+        let terra = TerraQuerier::new(&wrapper);
+
+        let res = terra
+            .query_exchange_rates("uusd", vec!["uluna", "ukrw"])
+            .unwrap();
+        assert_eq!(
+            vec![
+                Decimal::from_ratio(1u128, 40u128),
+                Decimal::from_ratio(1200u128, 1u128)
+            ],
+            res.exchange_rates
+                .iter()
+                .map(|item| item.exchange_rate)
+                .collect::<Vec<_>>()
+        );
+        terra
+            .query_exchange_rates("uusd", vec!["ueur"])
+            .unwrap_err();
+
+        let res = terra.query_swap(coin(100, "uusd"), "ukrw").unwrap();
+        assert_eq!(coin(120000, "ukrw"), res.receive);
+        let res = terra.query_swap(coin(99, "uusd"), "uluna").unwrap();
+        assert_eq!(coin(2, "uluna"), res.receive);
+        terra.query_swap(coin(100, "uusd"), "uusd").unwrap_err();
+
+        assert_eq!(Decimal::permille(5), terra.query_tax_rate().unwrap().rate);
+        let cap = terra.query_tax_cap("uusd").unwrap().cap;
+        assert_eq!(Uint128::new(1_000_000), cap);
+        terra.query_tax_cap("ukrw").unwrap_err();
+
+        assert_eq!(
+            coin(5, "uusd"),
+            wrapper.query_balance("alice", "uusd").unwrap()
+        );
+    }
+
+    #[test]
+    fn answers_staking_and_wasm_queries_from_fixtures() {
+        let mut querier = TerraMockQuerier::default();
+        let validator = Validator {
+            address: "validator".to_string(),
+            commission: Decimal::percent(1),
+            max_commission: Decimal::percent(10),
+            max_change_rate: Decimal::percent(1),
+        };
+        let delegation = FullDelegation {
+            delegator: Addr::unchecked("alice"),
+            validator: "validator".to_string(),
+            amount: coin(100, LUNA_DENOM),
+            can_redelegate: coin(100, LUNA_DENOM),
+            accumulated_rewards: coins(3, "uusd"),
+        };
+        querier.update_staking(&[validator], std::slice::from_ref(&delegation));
+        querier.update_token_balance("token", "alice", 7);
+        let wrapper = QuerierWrapper::new(&querier);
+
+        assert_eq!(LUNA_DENOM, wrapper.query_bonded_denom().unwrap());
+        let res = wrapper.query_delegation("alice", "validator").unwrap();
+        assert_eq!(Some(delegation), res);
+        assert_eq!(None, wrapper.query_delegation("bob", "validator").unwrap());
+
+        let token = TokenContract(Addr::unchecked("token"));
+        assert_eq!(
+            Uint128::new(7),
+            token.query_balance(&wrapper, "alice").unwrap()
+        );
+        let err = token.query_balance(&wrapper, "bob").unwrap_err();
+        assert!(matches!(err, TokenQueryError::NotCw20 { .. }));
+        let missing = TokenContract(Addr::unchecked("missing"));
+        let err = missing.query_balance(&wrapper, "alice").unwrap_err();
+        assert_eq!(
+            TokenQueryError::ContractNotFound {
+                contract_addr: "missing".to_string()
+            },
+            err
+        );
+    }
+}
@@ -1,8 +1,488 @@
+use cosmwasm_std::{
+    to_binary, Addr, Binary, CosmosMsg, Decimal, QuerierWrapper, QueryRequest, StdResult,
+    Timestamp, Uint128, WasmMsg, WasmQuery,
+};
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 
-// We define a custom struct for each query response
+use crate::access::{AccessExecuteMsg, AccessQueryMsg};
+
+// The asset being priced by a feed
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum AssetId {
+    Cw20 { contract_addr: String },
+    Native { denom: String },
+}
+
+// A feed prices one asset in terms of a native quote denom, e.g. AURM in uluna
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct FeedId {
+    pub asset: AssetId,
+    pub quote_denom: String,
+}
+
+// Which price a query returns: the aggregated spot price, its exponential
+// moving average, or the lower or higher of the two for conservative pricing
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum PriceKind {
+    Spot,
+    Ema,
+    Min,
+    Max,
+}
+
+// Aggregated price of an oracle feed, as returned by the oracle's QueryPriceV2
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 pub struct PriceResponse {
+    pub price: Decimal,
+    pub decimals: u8,
+    pub quote_denom: String,
+    // Median absolute deviation of the contributing prices from `price`. Zero
+    // with a single publisher, consumers can compare it to `price` to decide
+    // whether the sources agree closely enough.
+    pub confidence: Decimal,
+    // Number of fresh submissions that went into the price
+    pub publishers: u32,
+    // Block of the newest submission that went into the price
+    pub last_updated_height: u64,
+    pub last_updated_time: Timestamp,
+}
+
+// Price as returned by the oracle's original QueryPrice, a whole number of the
+// quote denom
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub struct LegacyPriceResponse {
     pub price: u64,
+    // Block of the newest submission that went into the price
+    pub last_updated_height: u64,
+    pub last_updated_time: Timestamp,
+}
+
+// Executed on every approved subscriber after the price of a feed changes.
+// Subscribers handle it as a variant of their own ExecuteMsg.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum HookMsg {
+    PriceUpdated { feed: FeedId, price: PriceResponse },
+}
+
+// Role allowed to pause the oracle, granted and revoked by the admin
+pub const GUARDIAN_ROLE: &str = "guardian";
+
+// Limits on the prices a feed accepts. `max_change` is a fraction of the last
+// recorded price, e.g. 0.1 allows moves of up to 10% per update. With
+// `hold_for_review` set, out-of-bound prices wait for the admin to confirm
+// them instead of being rejected.
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq, JsonSchema)]
+pub struct PriceBounds {
+    pub min_price: Option<Decimal>,
+    pub max_price: Option<Decimal>,
+    pub max_change: Option<Decimal>,
+    #[serde(default)]
+    pub hold_for_review: bool,
+}
+
+// Publishers need `min_bond` uluna bonded to submit prices. A submission more
+// than `max_deviation` away from the median earns a strike, and after
+// `strikes_to_slash` strikes `slash_ratio` of the bond goes to the treasury.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct BondingConfig {
+    pub min_bond: Uint128,
+    pub max_deviation: Decimal,
+    pub strikes_to_slash: u32,
+    pub slash_ratio: Decimal,
+    pub treasury: String,
+    pub unbonding_seconds: u64,
+}
+
+// A publisher may update a feed at most once every `min_interval` seconds,
+// unless its price moved by more than `override_deviation` since its last
+// update
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct RateLimit {
+    pub min_interval: u64,
+    pub override_deviation: Option<Decimal>,
+}
+
+// Feeds with rounds configured only take prices through commit–reveal rounds.
+// Each round accepts commitments for `commit_seconds` after it starts and
+// reveals for `reveal_seconds` after that.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct RoundConfig {
+    pub commit_seconds: u64,
+    pub reveal_seconds: u64,
+}
+
+// Where the reference price of a feed on Terra's native oracle comes from
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum NativeSource {
+    // Exchange rate of the feed's native asset in its quote denom
+    ExchangeRate {},
+    // Price of the same asset on another feed, converted at the exchange rate
+    // between the two quote denoms, e.g. AURM/uusd from AURM/uluna and LUNA/UST
+    Chained { via: FeedId },
+}
+
+// Updates whose price is further than `tolerance` from the native reference
+// are rejected, e.g. 0.05 for 5% of the reference price
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct NativeReference {
+    pub source: NativeSource,
+    pub tolerance: Decimal,
+}
+
+// Price signed off-chain. Signers sign the SHA-256 hash of the JSON-encoded
+// attestation, which relayers pass on unchanged as `payload`. `timestamp` is
+// in seconds and, together with `nonce`, must increase with every attestation.
+// `oracle` and `chain_id` name the one deployment the price is meant for.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct Attestation {
+    pub oracle: String,
+    pub chain_id: String,
+    pub feed: FeedId,
+    pub price: Decimal,
+    pub timestamp: u64,
+    pub nonce: u64,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct InstantiateMsg {
+    pub price: Decimal,
+    // Feed registered at instantiation, used whenever a message omits its feed
+    pub default_feed: FeedId,
+    pub decimals: u8,
+    // Publishers approved in addition to the instantiator, who seeds `price`
+    #[serde(default)]
+    pub publishers: Vec<String>,
+    // Defaults to the instantiator
+    pub admin: Option<String>,
+    // Defaults to one hour when not set
+    pub max_price_age: Option<u64>,
+    // Observations kept per feed, defaults to 100
+    pub history_size: Option<u32>,
+}
+
+// Every `feed` left out below refers to the default feed
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum ExecuteMsg {
+    UpdatePrice {
+        feed: Option<FeedId>,
+        price: Decimal,
+    },
+    // Updates several feeds at once, either all of them or none
+    BatchUpdate {
+        updates: Vec<(FeedId, Decimal)>,
+    },
+    // Hands the admin role over and grants or revokes GUARDIAN_ROLE. The
+    // guardians may pause the oracle but never unpause it.
+    Access(AccessExecuteMsg),
+    // Enables bonding requirements for publishers, or lifts them with `None`
+    SetBondingConfig {
+        config: Option<BondingConfig>,
+    },
+    // Bonds the uluna sent along
+    Bond {},
+    // Starts unbonding, the amount can be withdrawn after the unbonding delay
+    Unbond {
+        amount: Uint128,
+    },
+    // Sends back all unbonded stake whose delay has passed
+    WithdrawUnbonded {},
+    // Freezes all prices: updates are rejected and price queries fail until
+    // the admin unpauses. Can be sent by the admin or a guardian.
+    Pause {},
+    Unpause {},
+    AddPublisher {
+        address: String,
+    },
+    RemovePublisher {
+        address: String,
+    },
+    AddSigner {
+        address: String,
+        public_key: Binary,
+    },
+    RemoveSigner {
+        address: String,
+    },
+    // Can be sent by anyone, the signature decides whether the price is accepted
+    SubmitAttestation {
+        signer: String,
+        payload: Binary,
+        signature: Binary,
+    },
+    // `decimals` is the precision of the feed, at most 18
+    RegisterFeed {
+        feed: FeedId,
+        decimals: u8,
+    },
+    RemoveFeed {
+        feed: FeedId,
+    },
+    SetPriceBounds {
+        feed: Option<FeedId>,
+        bounds: PriceBounds,
+    },
+    // The EMA moves half way towards a new spot price in `half_life_seconds`
+    SetEmaHalfLife {
+        feed: Option<FeedId>,
+        half_life_seconds: u64,
+    },
+    // A feed is expected to be updated at least every `heartbeat_seconds`
+    SetHeartbeat {
+        feed: Option<FeedId>,
+        heartbeat_seconds: u64,
+    },
+    // Checks updates of a feed against Terra's native exchange rates, or stops
+    // checking with `None`
+    SetNativeReference {
+        feed: Option<FeedId>,
+        reference: Option<NativeReference>,
+    },
+    // Limits how often each publisher may update a feed, or lifts the limit
+    // with `None`
+    SetRateLimit {
+        feed: Option<FeedId>,
+        rate_limit: Option<RateLimit>,
+    },
+    // Enables commit–reveal rounds on a feed, or disables them with `None`
+    SetRoundConfig {
+        feed: Option<FeedId>,
+        config: Option<RoundConfig>,
+    },
+    // Opens the commit window of a new round, once the previous one is finalized
+    StartRound {
+        feed: Option<FeedId>,
+    },
+    // `commitment` is the SHA-256 hash of
+    // "<publisher>:<feed>:<round>:<price>:<salt>", e.g.
+    // "terra1...:native:uluna/uusd:3:1.25:f00d", with the feed written as in
+    // the `feed` attributes and the price written the way it is revealed.
+    // Binding the publisher and round keeps others from copying the commitment.
+    CommitPrice {
+        feed: Option<FeedId>,
+        commitment: Binary,
+    },
+    RevealPrice {
+        feed: Option<FeedId>,
+        price: Decimal,
+        salt: String,
+    },
+    // Aggregates the revealed prices once the reveal window is over. Can be
+    // sent by anyone.
+    FinalizeRound {
+        feed: Option<FeedId>,
+    },
+    // Asks to receive a `PriceUpdated` hook after every update of a feed,
+    // starting once the admin approves
+    Subscribe {
+        feed: Option<FeedId>,
+    },
+    Unsubscribe {
+        feed: Option<FeedId>,
+    },
+    ApproveSubscriber {
+        feed: Option<FeedId>,
+        subscriber: String,
+    },
+    RemoveSubscriber {
+        feed: Option<FeedId>,
+        subscriber: String,
+    },
+    // Accepts a price held for review as if the publisher had just sent it
+    ConfirmPendingPrice {
+        feed: Option<FeedId>,
+        publisher: String,
+    },
+    DiscardPendingPrice {
+        feed: Option<FeedId>,
+        publisher: String,
+    },
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum QueryMsg {
+    QueryRoles {},
+    // Lists the guardians through RoleMembers with GUARDIAN_ROLE
+    Access(AccessQueryMsg),
+    QueryBondingConfig {},
+    QueryBond {
+        address: String,
+    },
+    QueryBonds {
+        start_after: Option<String>,
+        limit: Option<u32>,
+    },
+    // QuerySlashes lists past slashes, newest first. `start_after` is the id of
+    // the last slash already seen.
+    QuerySlashes {
+        start_after: Option<u64>,
+        limit: Option<u32>,
+    },
+    // QueryPrice returns the median of all fresh submissions for a feed. It
+    // fails when the newest submission is older than `max_age_seconds`, if given.
+    // The price is rounded down to a whole number of the quote denom; use
+    // QueryPriceV2 for the exact decimal price.
+    QueryPrice {
+        feed: Option<FeedId>,
+        max_age_seconds: Option<u64>,
+    },
+    // `kind` defaults to the spot price
+    QueryPriceV2 {
+        feed: Option<FeedId>,
+        max_age_seconds: Option<u64>,
+        kind: Option<PriceKind>,
+    },
+    // QueryEmaPrice returns the exponential moving average of a feed's price
+    // next to its spot price
+    QueryEmaPrice {
+        feed: Option<FeedId>,
+    },
+    // QueryDerivedPrice converts the price of a feed to another native denom
+    // using Terra's exchange rates
+    QueryDerivedPrice {
+        feed: Option<FeedId>,
+        quote_denom: String,
+        max_age_seconds: Option<u64>,
+    },
+    // QueryPriceHistory returns past aggregated prices of a feed, newest first.
+    // `start_after` is the sequence number of the last observation already seen.
+    QueryPriceHistory {
+        feed: Option<FeedId>,
+        start_after: Option<u64>,
+        limit: Option<u32>,
+    },
+    // QueryTwap returns the time-weighted average price over the last
+    // `window_seconds`, based on the recorded history
+    QueryTwap {
+        feed: Option<FeedId>,
+        window_seconds: u64,
+    },
+    // QueryPendingPrices lists the prices held for review on a feed
+    QueryPendingPrices {
+        feed: Option<FeedId>,
+    },
+    QueryFeed {
+        feed: FeedId,
+    },
+    // QueryFeeds pages through all registered feeds
+    QueryFeeds {
+        start_after: Option<FeedId>,
+        limit: Option<u32>,
+    },
+    QueryFeedHealth {
+        feed: Option<FeedId>,
+    },
+    // QueryFeedsByStaleness pages through the health of all feeds, most
+    // overdue first
+    QueryFeedsByStaleness {
+        start_after: Option<FeedId>,
+        limit: Option<u32>,
+    },
+    // QueryPublishers lists approved publishers with their last submission
+    QueryPublishers {
+        feed: Option<FeedId>,
+        start_after: Option<String>,
+        limit: Option<u32>,
+    },
+    // QueryPublisherPrice returns the last submission of a publisher or signer
+    QueryPublisherPrice {
+        feed: Option<FeedId>,
+        address: String,
+    },
+    QuerySigners {
+        start_after: Option<String>,
+        limit: Option<u32>,
+    },
+    // QuerySubscribers lists the subscribers of a feed, approved or not
+    QuerySubscribers {
+        feed: Option<FeedId>,
+        start_after: Option<String>,
+        limit: Option<u32>,
+    },
+    // QueryRound returns a round with its phase, the current round if `round`
+    // is not given
+    QueryRound {
+        feed: Option<FeedId>,
+        round: Option<u64>,
+    },
+    // QueryFinalizedRounds pages through finalized rounds, newest first.
+    // `start_after` is the id of the last round already seen.
+    QueryFinalizedRounds {
+        feed: Option<FeedId>,
+        start_after: Option<u64>,
+        limit: Option<u32>,
+    },
+}
+
+/// Typed client for the oracle contract at the wrapped address
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct OracleContract(pub Addr);
+
+impl OracleContract {
+    pub fn addr(&self) -> Addr {
+        self.0.clone()
+    }
+
+    /// Queries the price of `feed`, or of the oracle's default feed with `None`.
+    /// `max_age_seconds` and `kind` default to the oracle's own settings.
+    pub fn query_price(
+        &self,
+        querier: &QuerierWrapper,
+        feed: Option<FeedId>,
+        max_age_seconds: Option<u64>,
+        kind: Option<PriceKind>,
+    ) -> StdResult<PriceResponse> {
+        self.query(
+            querier,
+            &QueryMsg::QueryPriceV2 {
+                feed,
+                max_age_seconds,
+                kind,
+            },
+        )
+    }
+
+    pub fn update_price<C>(&self, feed: Option<FeedId>, price: Decimal) -> StdResult<CosmosMsg<C>>
+    where
+        C: Clone + std::fmt::Debug + PartialEq + JsonSchema,
+    {
+        self.call(&ExecuteMsg::UpdatePrice { feed, price })
+    }
+
+    pub fn batch_update<C>(&self, updates: Vec<(FeedId, Decimal)>) -> StdResult<CosmosMsg<C>>
+    where
+        C: Clone + std::fmt::Debug + PartialEq + JsonSchema,
+    {
+        self.call(&ExecuteMsg::BatchUpdate { updates })
+    }
+
+    fn call<C>(&self, msg: &ExecuteMsg) -> StdResult<CosmosMsg<C>>
+    where
+        C: Clone + std::fmt::Debug + PartialEq + JsonSchema,
+    {
+        Ok(WasmMsg::Execute {
+            contract_addr: self.addr().into(),
+            msg: to_binary(msg)?,
+            funds: vec![],
+        }
+        .into())
+    }
+
+    fn query<T: serde::de::DeserializeOwned>(
+        &self,
+        querier: &QuerierWrapper,
+        msg: &QueryMsg,
+    ) -> StdResult<T> {
+        querier.query(&QueryRequest::Wasm(WasmQuery::Smart {
+            contract_addr: self.addr().into(),
+            msg: to_binary(msg)?,
+        }))
+    }
 }
@@ -1,8 +1,9 @@
 use cosmwasm_std::{
-    to_binary, Addr, AllBalanceResponse, BalanceResponse, BankQuery, Coin, QuerierWrapper,
-    QueryRequest, StdResult, Uint128, WasmQuery,
+    Addr, AllBalanceResponse, BalanceResponse, BankQuery, Coin, QuerierWrapper, QueryRequest,
+    StdResult, Uint128,
 };
-use cw20::{BalanceResponse as Cw20BalanceResponse, Cw20QueryMsg, TokenInfoResponse};
+
+use crate::token::{TokenContract, TokenQueryError};
 /// ## Description
 /// Returns the balance of the denom at the specified account address.
 /// ## Params
@@ -37,32 +38,89 @@ pub fn query_all_balances(querier: &QuerierWrapper, account_addr: &Addr) -> StdR
     Ok(all_balances.amount)
 }
 /// ## Description
-/// Returns the token balance at the specified contract address.
+/// Returns the token balance at the specified contract address. Fails with
+/// [`TokenQueryError::ContractNotFound`] or [`TokenQueryError::NotCw20`] rather than reading
+/// a wrong address as an empty balance.
 /// ## Params
 /// * **querier** is the object of type [`QuerierWrapper`].
 ///
 /// * **contract_addr** is the object of type [`Addr`]. Sets the address of the contract for which
-/// the balance will be requested
+///   the balance will be requested
 ///
 /// * **account_addr** is the object of type [`Addr`].
 pub fn query_token_balance(
     querier: &QuerierWrapper,
     contract_addr: &Addr,
     account_addr: &Addr,
-) -> StdResult<Uint128> {
-    // load balance from the token contract
-    let res: Cw20BalanceResponse = querier
-        .query(&QueryRequest::Wasm(WasmQuery::Smart {
-            contract_addr: String::from(contract_addr),
-            msg: to_binary(&Cw20QueryMsg::Balance {
-                address: String::from(account_addr),
-            })?,
-        }))
-        .unwrap_or_else(|_| Cw20BalanceResponse {
-            balance: Uint128::zero(),
-        });
-
-    Ok(res.balance)
+) -> Result<Uint128, TokenQueryError> {
+    TokenContract(contract_addr.clone()).query_balance(querier, account_addr)
+}
+
+/// ## Description
+/// Returns the token balance at the specified contract address, or zero if the query fails for
+/// any reason. Only for callers that really can't tell a failed query from an empty balance,
+/// prefer [`query_token_balance`].
+/// ## Params
+/// * **querier** is the object of type [`QuerierWrapper`].
+///
+/// * **contract_addr** is the object of type [`Addr`].
+///
+/// * **account_addr** is the object of type [`Addr`].
+pub fn query_token_balance_or_zero(
+    querier: &QuerierWrapper,
+    contract_addr: &Addr,
+    account_addr: &Addr,
+) -> Uint128 {
+    query_token_balance(querier, contract_addr, account_addr).unwrap_or_else(|_| Uint128::zero())
+}
+
+/// ## Description
+/// Returns the balances of many holders of one token, in the order of `account_addrs`. Fails as
+/// a whole if any query fails.
+/// ## Params
+/// * **querier** is the object of type [`QuerierWrapper`].
+///
+/// * **contract_addr** is the object of type [`Addr`].
+///
+/// * **account_addrs** are the holders to query.
+pub fn query_token_balances(
+    querier: &QuerierWrapper,
+    contract_addr: &Addr,
+    account_addrs: &[Addr],
+) -> Result<Vec<(Addr, Uint128)>, TokenQueryError> {
+    let token = TokenContract(contract_addr.clone());
+    account_addrs
+        .iter()
+        .map(|account_addr| {
+            Ok((
+                account_addr.clone(),
+                token.query_balance(querier, account_addr)?,
+            ))
+        })
+        .collect()
+}
+
+/// ## Description
+/// Returns the balances one account holds of many tokens, in the order of `contract_addrs`.
+/// Fails as a whole if any query fails.
+/// ## Params
+/// * **querier** is the object of type [`QuerierWrapper`].
+///
+/// * **contract_addrs** are the token contracts to query.
+///
+/// * **account_addr** is the object of type [`Addr`].
+pub fn query_balances_of_tokens(
+    querier: &QuerierWrapper,
+    contract_addrs: &[Addr],
+    account_addr: &Addr,
+) -> Result<Vec<(Addr, Uint128)>, TokenQueryError> {
+    contract_addrs
+        .iter()
+        .map(|contract_addr| {
+            let balance = query_token_balance(querier, contract_addr, account_addr)?;
+            Ok((contract_addr.clone(), balance))
+        })
+        .collect()
 }
 
 /// ## Description
@@ -72,10 +130,7 @@ pub fn query_token_balance(
 ///
 /// * **contract_addr** is the object of type [`Addr`].
 pub fn query_token_symbol(querier: &QuerierWrapper, contract_addr: &Addr) -> StdResult<String> {
-    let res: TokenInfoResponse = querier.query(&QueryRequest::Wasm(WasmQuery::Smart {
-        contract_addr: String::from(contract_addr),
-        msg: to_binary(&Cw20QueryMsg::TokenInfo {})?,
-    }))?;
+    let res = TokenContract(contract_addr.clone()).query_info(querier)?;
 
     Ok(res.symbol)
 }
@@ -87,9 +142,82 @@ pub fn query_token_symbol(querier: &QuerierWrapper, contract_addr: &Addr) -> Std
 ///
 /// * **contract_addr** is the object of type [`Addr`].
 pub fn query_supply(querier: &QuerierWrapper, contract_addr: &Addr) -> StdResult<Uint128> {
-    let res: TokenInfoResponse = querier.query(&QueryRequest::Wasm(WasmQuery::Smart {
-        contract_addr: String::from(contract_addr),
-        msg: to_binary(&Cw20QueryMsg::TokenInfo {})?,
-    }))?;
+    let res = TokenContract(contract_addr.clone()).query_info(querier)?;
     Ok(res.total_supply)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use cosmwasm_std::{
+        from_binary, from_slice, to_binary, ContractResult, Empty, Querier, QuerierResult,
+        SystemError, SystemResult, WasmQuery,
+    };
+    use cw20::{BalanceResponse as Cw20BalanceResponse, Cw20QueryMsg};
+
+    // "token" holds 100 for alice, "pool" answers but isn't a cw20 token, and
+    // no other contract exists
+    struct TokenQuerier;
+
+    impl Querier for TokenQuerier {
+        fn raw_query(&self, bin_request: &[u8]) -> QuerierResult {
+            let (contract_addr, msg) = match from_slice(bin_request).unwrap() {
+                QueryRequest::<Empty>::Wasm(WasmQuery::Smart { contract_addr, msg }) => {
+                    (contract_addr, msg)
+                }
+                request => panic!("unexpected query {:?}", request),
+            };
+            match contract_addr.as_str() {
+                "token" => {
+                    let balance = match from_binary(&msg).unwrap() {
+                        Cw20QueryMsg::Balance { address } if address == "alice" => 100u128,
+                        _ => 0,
+                    };
+                    let res = Cw20BalanceResponse {
+                        balance: Uint128::new(balance),
+                    };
+                    SystemResult::Ok(ContractResult::Ok(to_binary(&res).unwrap()))
+                }
+                "pool" => SystemResult::Ok(ContractResult::Err("unknown variant".to_string())),
+                _ => SystemResult::Err(SystemError::NoSuchContract {
+                    addr: contract_addr,
+                }),
+            }
+        }
+    }
+
+    #[test]
+    fn token_balance_errors_are_kept_apart_from_zero() {
+        let querier = TokenQuerier;
+        let querier = QuerierWrapper::new(&querier);
+        let alice = Addr::unchecked("alice");
+        let bob = Addr::unchecked("bob");
+
+        let token = Addr::unchecked("token");
+        let balance = query_token_balance(&querier, &token, &bob).unwrap();
+        assert_eq!(Uint128::zero(), balance);
+
+        let missing = Addr::unchecked("missing");
+        let err = query_token_balance(&querier, &missing, &alice).unwrap_err();
+        assert_eq!(
+            TokenQueryError::ContractNotFound {
+                contract_addr: "missing".to_string()
+            },
+            err
+        );
+        let pool = Addr::unchecked("pool");
+        let err = query_token_balance(&querier, &pool, &alice).unwrap_err();
+        assert!(matches!(err, TokenQueryError::NotCw20 { .. }));
+
+        // Only the explicit opt-in reads failures as empty balances
+        let balance = query_token_balance_or_zero(&querier, &missing, &alice);
+        assert_eq!(Uint128::zero(), balance);
+
+        let balances = query_token_balances(&querier, &token, &[alice.clone(), bob]).unwrap();
+        assert_eq!(Uint128::new(100), balances[0].1);
+        assert_eq!(Uint128::zero(), balances[1].1);
+
+        let err = query_balances_of_tokens(&querier, &[token, pool], &alice).unwrap_err();
+        assert!(matches!(err, TokenQueryError::NotCw20 { .. }));
+    }
+}
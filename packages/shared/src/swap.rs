@@ -0,0 +1,113 @@
+use cosmwasm_std::{
+    to_binary, Addr, Coin, CosmosMsg, QuerierWrapper, QueryRequest, StdResult, WasmMsg, WasmQuery,
+};
+use cw20::Cw20ReceiveMsg;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use crate::access::{AccessExecuteMsg, AccessQueryMsg};
+use crate::asset::AssetInfoUnchecked;
+use crate::oracle::FeedId;
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub struct TokenAddrResponse {
+    pub token_address: Addr,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub struct InstantiateMsg {
+    pub oracle_address: Addr,
+    // Asset buyers pay with, e.g. uluna
+    pub offer_asset: AssetInfoUnchecked,
+    // Asset buyers get in return, e.g. AURM
+    pub ask_asset: AssetInfoUnchecked,
+    // Oracle feed pricing the ask asset in the offer asset, the oracle's
+    // default feed if not set
+    #[serde(default)]
+    pub feed: Option<FeedId>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum ExecuteMsg {
+    // Buy with a native offer asset sent along as funds
+    Buy {},
+    // Buy with a cw20 offer asset, sent through the token's Send
+    Receive(Cw20ReceiveMsg),
+    // Withdraw the offer asset collected so far
+    Withdraw { amount: i32 },
+    // Hands over ownership, and with it the right to withdraw
+    Access(AccessExecuteMsg),
+}
+
+// Message embedded in the cw20 Send of the offer asset
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum ReceiveMsg {
+    Buy {},
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum QueryMsg {
+    /// Returns Token Address
+    GetTokenAddr,
+
+    /// Returns balance of native token
+    GetBalance,
+
+    /// Returns the offer and ask assets and the price feed
+    GetAssets,
+
+    /// Returns the owner and role members
+    Access(AccessQueryMsg),
+}
+
+/// Typed client for the swap contract at the wrapped address
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct SwapContract(pub Addr);
+
+impl SwapContract {
+    pub fn addr(&self) -> Addr {
+        self.0.clone()
+    }
+
+    /// Queries the address of the token the contract sells
+    pub fn query_token_address(&self, querier: &QuerierWrapper) -> StdResult<Addr> {
+        let res: TokenAddrResponse = querier.query(&QueryRequest::Wasm(WasmQuery::Smart {
+            contract_addr: self.addr().into(),
+            msg: to_binary(&QueryMsg::GetTokenAddr)?,
+        }))?;
+        Ok(res.token_address)
+    }
+
+    /// Buys the ask asset with `funds`, which have to be the native offer asset
+    pub fn buy<C>(&self, funds: Vec<Coin>) -> StdResult<CosmosMsg<C>>
+    where
+        C: Clone + std::fmt::Debug + PartialEq + JsonSchema,
+    {
+        self.call(&ExecuteMsg::Buy {}, funds)
+    }
+
+    /// Withdraws `amount` of the collected offer asset, only for the owner
+    pub fn withdraw<C>(&self, amount: i32) -> StdResult<CosmosMsg<C>>
+    where
+        C: Clone + std::fmt::Debug + PartialEq + JsonSchema,
+    {
+        self.call(&ExecuteMsg::Withdraw { amount }, vec![])
+    }
+
+    fn call<C>(&self, msg: &ExecuteMsg, funds: Vec<Coin>) -> StdResult<CosmosMsg<C>>
+    where
+        C: Clone + std::fmt::Debug + PartialEq + JsonSchema,
+    {
+        Ok(WasmMsg::Execute {
+            contract_addr: self.addr().into(),
+            msg: to_binary(msg)?,
+            funds,
+        }
+        .into())
+    }
+}
@@ -0,0 +1,272 @@
+use anyhow::Result as AnyResult;
+use cosmwasm_std::testing::{mock_env, MockApi, MockStorage};
+use cosmwasm_std::{
+    Addr, Api, CanonicalAddr, Coin, Decimal, Empty, RecoverPubkeyError, StdResult, Uint128,
+    VerificationError,
+};
+use cw20::Cw20Coin;
+use cw20_base::msg::InstantiateMsg as TokenInstantiateMsg;
+use cw_multi_test::{App, AppResponse, BankKeeper, Contract, Executor};
+
+use crate::asset::AssetInfoUnchecked;
+use crate::oracle::{AssetId, FeedId, InstantiateMsg as OracleInstantiateMsg, OracleContract};
+use crate::swap::{InstantiateMsg as SwapInstantiateMsg, SwapContract};
+use crate::token::TokenContract;
+
+pub const OWNER: &str = "owner";
+pub const OFFER_DENOM: &str = "uluna";
+
+// Code of the workspace contracts. The contracts depend on this crate, so
+// the tests deploying them have to hand their entry points in.
+pub struct Codes {
+    pub oracle: Box<dyn Contract<Empty>>,
+    pub token: Box<dyn Contract<Empty>>,
+    pub swap: Box<dyn Contract<Empty>>,
+}
+
+// MockApi, except that it accepts the mixed case "Contract #0" addresses
+// cw-multi-test assigns
+struct TestApi(MockApi);
+
+impl Api for TestApi {
+    fn addr_validate(&self, human: &str) -> StdResult<Addr> {
+        self.0.addr_canonicalize(human)?;
+        Ok(Addr::unchecked(human))
+    }
+
+    fn addr_canonicalize(&self, human: &str) -> StdResult<CanonicalAddr> {
+        self.0.addr_canonicalize(human)
+    }
+
+    fn addr_humanize(&self, canonical: &CanonicalAddr) -> StdResult<Addr> {
+        self.0.addr_humanize(canonical)
+    }
+
+    fn secp256k1_verify(
+        &self,
+        message_hash: &[u8],
+        signature: &[u8],
+        public_key: &[u8],
+    ) -> Result<bool, VerificationError> {
+        self.0.secp256k1_verify(message_hash, signature, public_key)
+    }
+
+    fn secp256k1_recover_pubkey(
+        &self,
+        message_hash: &[u8],
+        signature: &[u8],
+        recovery_param: u8,
+    ) -> Result<Vec<u8>, RecoverPubkeyError> {
+        self.0
+            .secp256k1_recover_pubkey(message_hash, signature, recovery_param)
+    }
+
+    fn ed25519_verify(
+        &self,
+        message: &[u8],
+        signature: &[u8],
+        public_key: &[u8],
+    ) -> Result<bool, VerificationError> {
+        self.0.ed25519_verify(message, signature, public_key)
+    }
+
+    fn ed25519_batch_verify(
+        &self,
+        messages: &[&[u8]],
+        signatures: &[&[u8]],
+        public_keys: &[&[u8]],
+    ) -> Result<bool, VerificationError> {
+        self.0
+            .ed25519_batch_verify(messages, signatures, public_keys)
+    }
+
+    fn debug(&self, message: &str) {
+        self.0.debug(message)
+    }
+}
+
+/// Deploys a token, an oracle pricing it in uluna and a swap selling it for
+/// uluna, all owned by `OWNER`
+pub struct SuiteBuilder {
+    price: Decimal,
+    supply: u128,
+    inventory: u128,
+    funds: Vec<(String, Vec<Coin>)>,
+}
+
+impl Default for SuiteBuilder {
+    fn default() -> Self {
+        SuiteBuilder {
+            price: Decimal::one(),
+            supply: 1_000_000_000,
+            inventory: 0,
+            funds: vec![],
+        }
+    }
+}
+
+impl SuiteBuilder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Oracle price of one token in uluna
+    pub fn with_price(mut self, price: Decimal) -> Self {
+        self.price = price;
+        self
+    }
+
+    /// Tokens minted to `OWNER` at instantiation
+    pub fn with_supply(mut self, supply: u128) -> Self {
+        self.supply = supply;
+        self
+    }
+
+    /// Tokens the swap contract starts out with, taken from `OWNER`
+    pub fn with_inventory(mut self, inventory: u128) -> Self {
+        self.inventory = inventory;
+        self
+    }
+
+    /// Native funds `account` starts out with
+    pub fn with_funds(mut self, account: &str, funds: &[Coin]) -> Self {
+        self.funds.push((account.to_string(), funds.to_vec()));
+        self
+    }
+
+    pub fn build(self, codes: Codes) -> Suite {
+        let mut app = App::new(
+            TestApi(MockApi::default()),
+            mock_env().block,
+            BankKeeper::new(),
+            MockStorage::new(),
+        );
+        for (account, funds) in self.funds {
+            app.init_bank_balance(&Addr::unchecked(account), funds)
+                .unwrap();
+        }
+        let owner = Addr::unchecked(OWNER);
+
+        let token_id = app.store_code(codes.token);
+        let token = app
+            .instantiate_contract(
+                token_id,
+                owner.clone(),
+                &TokenInstantiateMsg {
+                    name: "Aurum".to_string(),
+                    symbol: "AURM".to_string(),
+                    decimals: 6,
+                    initial_balances: vec![Cw20Coin {
+                        address: OWNER.to_string(),
+                        amount: Uint128::new(self.supply),
+                    }],
+                    mint: None,
+                    marketing: None,
+                },
+                &[],
+                "token",
+                None,
+            )
+            .unwrap();
+
+        let oracle_id = app.store_code(codes.oracle);
+        let oracle = app
+            .instantiate_contract(
+                oracle_id,
+                owner.clone(),
+                &OracleInstantiateMsg {
+                    price: self.price,
+                    default_feed: FeedId {
+                        asset: AssetId::Cw20 {
+                            contract_addr: token.to_string(),
+                        },
+                        quote_denom: OFFER_DENOM.to_string(),
+                    },
+                    decimals: 6,
+                    publishers: vec![],
+                    admin: None,
+                    max_price_age: None,
+                    history_size: None,
+                },
+                &[],
+                "oracle",
+                None,
+            )
+            .unwrap();
+
+        let swap_id = app.store_code(codes.swap);
+        let swap = app
+            .instantiate_contract(
+                swap_id,
+                owner.clone(),
+                &SwapInstantiateMsg {
+                    oracle_address: oracle.clone(),
+                    offer_asset: AssetInfoUnchecked::native(OFFER_DENOM),
+                    ask_asset: AssetInfoUnchecked::cw20(token.as_str()),
+                    feed: None,
+                },
+                &[],
+                "swap",
+                None,
+            )
+            .unwrap();
+
+        let mut suite = Suite {
+            app,
+            owner,
+            oracle: OracleContract(oracle),
+            token: TokenContract(token),
+            swap: SwapContract(swap),
+        };
+        if self.inventory > 0 {
+            suite.stock_inventory(self.inventory).unwrap();
+        }
+        suite
+    }
+}
+
+pub struct Suite {
+    pub app: App,
+    pub owner: Addr,
+    pub oracle: OracleContract,
+    pub token: TokenContract,
+    pub swap: SwapContract,
+}
+
+impl Suite {
+    /// Publishes `price` on the oracle's default feed as `OWNER`
+    pub fn set_price(&mut self, price: Decimal) -> AnyResult<AppResponse> {
+        let msg = self.oracle.update_price(None, price)?;
+        self.app.execute(self.owner.clone(), msg)
+    }
+
+    /// Moves `amount` tokens from `OWNER` into the swap contract
+    pub fn stock_inventory(&mut self, amount: u128) -> AnyResult<AppResponse> {
+        let msg = self
+            .token
+            .transfer(self.swap.addr(), Uint128::new(amount))?;
+        self.app.execute(self.owner.clone(), msg)
+    }
+
+    pub fn buy(&mut self, buyer: &str, funds: &[Coin]) -> AnyResult<AppResponse> {
+        let msg = self.swap.buy(funds.to_vec())?;
+        self.app.execute(Addr::unchecked(buyer), msg)
+    }
+
+    pub fn withdraw(&mut self, sender: &str, amount: i32) -> AnyResult<AppResponse> {
+        let msg = self.swap.withdraw(amount)?;
+        self.app.execute(Addr::unchecked(sender), msg)
+    }
+
+    pub fn token_balance(&self, account: impl Into<String>) -> Uint128 {
+        self.token.query_balance(&self.app.wrap(), account).unwrap()
+    }
+
+    pub fn native_balance(&self, account: impl Into<String>) -> Uint128 {
+        self.app
+            .wrap()
+            .query_balance(account, OFFER_DENOM)
+            .unwrap()
+            .amount
+    }
+}
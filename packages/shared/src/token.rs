@@ -0,0 +1,108 @@
+use cosmwasm_std::{
+    from_binary, to_binary, to_vec, Addr, ContractResult, CosmosMsg, Empty, QuerierWrapper,
+    QueryRequest, StdError, StdResult, SystemError, SystemResult, Uint128, WasmMsg, WasmQuery,
+};
+use cw20::{BalanceResponse, Cw20ExecuteMsg, Cw20QueryMsg, TokenInfoResponse};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+/// Why a token query failed. A missing contract and a contract that doesn't
+/// answer like a cw20 token are told apart from other failures.
+#[derive(Error, Debug, PartialEq)]
+pub enum TokenQueryError {
+    #[error("{0}")]
+    Std(#[from] StdError),
+
+    #[error("No contract at {contract_addr}")]
+    ContractNotFound { contract_addr: String },
+
+    #[error("{contract_addr} is not a cw20 token: {reason}")]
+    NotCw20 {
+        contract_addr: String,
+        reason: String,
+    },
+}
+
+/// Typed client for the cw20 token contract at the wrapped address
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct TokenContract(pub Addr);
+
+impl TokenContract {
+    pub fn addr(&self) -> Addr {
+        self.0.clone()
+    }
+
+    /// Queries the token balance of `account`. Fails unless the contract exists
+    /// and answers like a cw20 token, so a zero balance is always a real one.
+    pub fn query_balance(
+        &self,
+        querier: &QuerierWrapper,
+        account: impl Into<String>,
+    ) -> Result<Uint128, TokenQueryError> {
+        let msg = Cw20QueryMsg::Balance {
+            address: account.into(),
+        };
+        let request: QueryRequest<Empty> = QueryRequest::Wasm(WasmQuery::Smart {
+            contract_addr: self.addr().into(),
+            msg: to_binary(&msg)?,
+        });
+
+        let not_cw20 = |reason: String| TokenQueryError::NotCw20 {
+            contract_addr: self.addr().into(),
+            reason,
+        };
+        let response = match querier.raw_query(&to_vec(&request)?) {
+            SystemResult::Ok(ContractResult::Ok(response)) => response,
+            SystemResult::Ok(ContractResult::Err(reason)) => return Err(not_cw20(reason)),
+            SystemResult::Err(SystemError::NoSuchContract { .. }) => {
+                return Err(TokenQueryError::ContractNotFound {
+                    contract_addr: self.addr().into(),
+                })
+            }
+            SystemResult::Err(error) => {
+                return Err(
+                    StdError::generic_err(format!("Querier system error: {}", error)).into(),
+                )
+            }
+        };
+        let res: BalanceResponse =
+            from_binary(&response).map_err(|error| not_cw20(error.to_string()))?;
+        Ok(res.balance)
+    }
+
+    /// Queries the name, symbol, decimals and total supply of the token
+    pub fn query_info(&self, querier: &QuerierWrapper) -> StdResult<TokenInfoResponse> {
+        self.query(querier, &Cw20QueryMsg::TokenInfo {})
+    }
+
+    pub fn transfer<C>(
+        &self,
+        recipient: impl Into<String>,
+        amount: Uint128,
+    ) -> StdResult<CosmosMsg<C>>
+    where
+        C: Clone + std::fmt::Debug + PartialEq + JsonSchema,
+    {
+        Ok(WasmMsg::Execute {
+            contract_addr: self.addr().into(),
+            msg: to_binary(&Cw20ExecuteMsg::Transfer {
+                recipient: recipient.into(),
+                amount,
+            })?,
+            funds: vec![],
+        }
+        .into())
+    }
+
+    fn query<T: serde::de::DeserializeOwned>(
+        &self,
+        querier: &QuerierWrapper,
+        msg: &Cw20QueryMsg,
+    ) -> StdResult<T> {
+        querier.query(&QueryRequest::Wasm(WasmQuery::Smart {
+            contract_addr: self.addr().into(),
+            msg: to_binary(msg)?,
+        }))
+    }
+}